@@ -3,6 +3,9 @@ precision mediump float;
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
+layout(location = 4) in vec4 Vertex_Color; // bevy puts mesh colors at 4
+
+layout(location = 0) out vec4 v_Color;
 
 layout(set = 0, binding = 0) uniform CameraViewProj {
     mat4 ViewProj;
@@ -23,4 +26,5 @@ layout(set = 2, binding = 0) uniform Mesh {
 
 void main() {
     gl_Position = ViewProj * Model * vec4(Vertex_Position, 1.0);
+    v_Color = Vertex_Color;
 }
@@ -4,16 +4,39 @@ use bevy::{
     render::{
         mesh::MeshVertexBufferLayout,
         render_resource::{
-            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+            AsBindGroup, Extent3d, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError, TextureDimension, TextureFormat,
         },
     },
     sprite::{Material2d, Material2dKey},
 };
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::VecDeque;
+
+use crate::{
+    physics::{
+        aperture::FarField,
+        bohm::GuidingWave,
+        detector::Detector,
+        export::Arrival,
+        fringe_shift,
+        histogram::{DensityGrid, DensityScale},
+        intensity_table,
+        matter::{display_wavelength, slit_unit, species},
+        sampling::{Hit, PatternSampler},
+        spectrum::spectrum,
+        wave::WaveField,
+    },
+    slit::wavelength_to_rgb,
+    units::{Distance, Length, Wavelength},
+};
 
 #[derive(Debug, Component)]
 pub enum InputType {
     Light,
     Particles,
+    // the wave itself on its way through the slits, see physics::wave
+    Wave,
 }
 
 impl std::fmt::Display for InputType {
@@ -22,97 +45,787 @@ impl std::fmt::Display for InputType {
     }
 }
 
+// how the open slits add up on the screen
+#[derive(Debug)]
+pub enum Coherence {
+    Coherent,
+    // as if each slit was opened on its own and the results piled up
+    Incoherent,
+}
+
+impl std::fmt::Display for Coherence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// which math to use for the pattern on the screen
+#[derive(Debug)]
+pub enum Diffraction {
+    // picks based on the fresnel number
+    Auto,
+    // far field
+    Fraunhofer,
+    // near field
+    Fresnel,
+}
+
+impl std::fmt::Display for Diffraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// what the light goes through. anything but Slits gets its pattern from an fft of a mask,
+// see physics::aperture
+#[derive(Debug)]
+pub enum Aperture {
+    Slits,
+    // slits that get wider from left to right
+    Uneven,
+    // 2d grid of square holes
+    Grid,
+    // one round hole
+    Circle,
+    // physics::aperture::CUSTOM_MASK
+    Custom,
+}
+
+impl std::fmt::Display for Aperture {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// something in front of SlitStructure::plate_slit that holds the wave back,
+// see physics::plate_phase
+#[derive(Debug)]
+pub enum Plate {
+    None,
+    // a straight phase shift, SlitStructure::plate_phase
+    Phase,
+    // a slab of glass, SlitStructure::plate_thickness and plate_index
+    Glass,
+}
+
+impl std::fmt::Display for Plate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// what the source's light is polarized like, see physics::polarization.
+// matter waves get the same treatment, think of it as spin
+#[derive(Debug)]
+pub enum Polarization {
+    Unpolarized,
+    Horizontal,
+    // 45 degrees, what the quantum eraser needs
+    Diagonal,
+    Vertical,
+    Circular,
+}
+
+impl std::fmt::Display for Polarization {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// what's shining on the slits, see physics::spectrum
+#[derive(Debug)]
+pub enum Source {
+    // one wavelength, SlitStructure::wavelength
+    Laser,
+    // the yellow doublet
+    Sodium,
+    Mercury,
+    // flat across the visible range
+    White,
+    // blackbody, like a light bulb
+    Incandescent,
+    // matter waves, see physics::matter
+    Electron,
+    Neutron,
+    Helium,
+    C60,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 //
 #[derive(Resource, Debug)]
 pub struct SlitStructure {
-    pub separation: f32,
-    pub slit_width: f32,
-    pub wavelength: f32,
-    pub screen_distance: f32,
+    pub aperture: Aperture,
+    pub separation: Length,
+    pub slit_count: u32,
+    pub covered: [bool; MAX_SLIT_COUNT as usize], // counting from the left
+    pub width_scales: [f32; MAX_SLIT_COUNT as usize], // times slit_width, per slit
+    pub transmissions: [f32; MAX_SLIT_COUNT as usize], // amplitude let through, 0 to 1
+    pub selected_slit: u32,                       // the one the One Slit page changes
+    pub plate: Plate,
+    pub plate_slit: u32,
+    pub plate_phase: f32, // degrees
+    pub plate_thickness: Length,
+    pub plate_index: f32, // refractive index of the glass
+    pub polarization: Polarization,
+    // linear polarizer angle in degrees over each slit, None for no polarizer
+    pub polarizers: [Option<f32>; MAX_SLIT_COUNT as usize],
+    pub analyzer: Option<f32>, // one more polarizer, right before the screen
+    pub coherence: Coherence,
+    pub which_path: f32, // how hard the detector at the slits looks, 0 to 1
+    pub tag_slits: bool, // color particles by the slit the detector caught them at
+    pub slit_width: Length,
+    pub source: Source,
+    pub wavelength: Wavelength, // only used by Source::Laser
+    pub voltage: f32,           // what charged matter waves get sped up through
+    pub velocity: f32,          // how fast neutral matter waves go
+    pub source_width: Length,
+    pub source_distance: Distance, // from the source to the slits
+    pub screen_distance: Distance,
+    pub diffraction: Diffraction,
     pub toggle_input: InputType,
+    pub seed: u64, // for the particles, the same seed gives the same run
 }
 
 impl Default for SlitStructure {
     fn default() -> Self {
         SlitStructure {
-            separation: 50.,       // micrometers
-            slit_width: 5.,        // micrometers
-            wavelength: 500.,      // nanometers
-            screen_distance: 100., // centimeters
+            aperture: Aperture::Slits,
+            separation: Length::micrometers(50.),
+            slit_count: 2,
+            covered: [false; MAX_SLIT_COUNT as usize],
+            width_scales: [1.; MAX_SLIT_COUNT as usize],
+            transmissions: [1.; MAX_SLIT_COUNT as usize],
+            selected_slit: 0,
+            plate: Plate::None,
+            plate_slit: 0,
+            plate_phase: 180.,
+            plate_thickness: Length::micrometers(1.),
+            plate_index: 1.5, // ordinary glass
+            polarization: Polarization::Unpolarized,
+            polarizers: [None; MAX_SLIT_COUNT as usize],
+            analyzer: None,
+            coherence: Coherence::Coherent,
+            which_path: 0.,
+            tag_slits: false,
+            slit_width: Length::micrometers(5.),
+            source: Source::Laser,
+            wavelength: Wavelength::nanometers(500.),
+            voltage: 50.,                          // kilovolts
+            velocity: 2200.,                       // meters per second
+            source_width: Length::micrometers(0.), // a perfect point source
+            source_distance: Distance::centimeters(10.),
+            screen_distance: Distance::centimeters(100.),
+            diffraction: Diffraction::Auto,
             toggle_input: InputType::Light,
+            seed: 0,
         }
     }
 }
 
-pub const MAX_WAVELENGTH: f32 = 800.;
-pub const MIN_WAVELENGTH: f32 = 200.;
+pub const MAX_WAVELENGTH: Wavelength = Wavelength::nanometers(800.);
+pub const MIN_WAVELENGTH: Wavelength = Wavelength::nanometers(200.);
+pub const MAX_SLIT_COUNT: u32 = 20;
 
 impl SlitStructure {
+    pub fn toggle_covered(&mut self, slit: u32) {
+        if slit < self.slit_count {
+            self.covered[slit as usize] = !self.covered[slit as usize];
+        }
+    }
+
+    pub fn open_slits(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.slit_count).filter(|&i| !self.covered[i as usize])
+    }
+
+    /// whether the first two slits have the quantum eraser's crossed polarizers
+    pub fn is_eraser(&self) -> bool {
+        self.polarizers[0] == Some(0.) && self.polarizers[1] == Some(90.)
+    }
+
     pub fn add_val(&mut self, opt: &SlitControl, val: f32) {
         match opt {
+            SlitControl::Aperture => {
+                self.aperture = match self.aperture {
+                    Aperture::Slits => Aperture::Uneven,
+                    Aperture::Uneven => Aperture::Grid,
+                    Aperture::Grid => Aperture::Circle,
+                    Aperture::Circle => Aperture::Custom,
+                    Aperture::Custom => Aperture::Slits,
+                }
+            }
             SlitControl::Separation => {
-                let new = self.separation + val;
-                if new >= 1. && new <= 100. {
-                    self.separation += val
+                let unit = slit_unit(self);
+                let new = self.separation + unit * val;
+                if (unit * 1. ..=unit * 100.).contains(&new) {
+                    self.separation = new
+                }
+            }
+            SlitControl::SlitCount => {
+                let new = self.slit_count as f32 + val;
+                if (1. ..=MAX_SLIT_COUNT as f32).contains(&new) {
+                    self.slit_count = new as u32;
+                    // the slits all move around, so start over with everything open
+                    self.covered = [false; MAX_SLIT_COUNT as usize];
+                    self.width_scales = [1.; MAX_SLIT_COUNT as usize];
+                    self.transmissions = [1.; MAX_SLIT_COUNT as usize];
+                    self.selected_slit = 0;
+                    self.plate_slit = 0;
+                    self.polarizers = [None; MAX_SLIT_COUNT as usize];
+                }
+            }
+            SlitControl::SelectedSlit => {
+                let new = self.selected_slit as f32 + val;
+                if (0. ..self.slit_count as f32).contains(&new) {
+                    self.selected_slit = new as u32;
+                }
+            }
+            SlitControl::SelectedWidth => {
+                let scale = &mut self.width_scales[self.selected_slit as usize];
+                // same rounding as which_path, so it can get back to exactly 1
+                let new = ((*scale + val) * 10.).round() / 10.;
+                if (0.2..=3.).contains(&new) {
+                    *scale = new
+                }
+            }
+            SlitControl::SelectedTransmission => {
+                let transmission = &mut self.transmissions[self.selected_slit as usize];
+                let new = ((*transmission + val) * 10.).round() / 10.;
+                if (0. ..=1.).contains(&new) {
+                    *transmission = new
+                }
+            }
+            SlitControl::SelectedPolarizer => {
+                let polarizer = &mut self.polarizers[self.selected_slit as usize];
+                *polarizer = next_polarizer(*polarizer)
+            }
+            SlitControl::Polarization => {
+                self.polarization = match self.polarization {
+                    Polarization::Unpolarized => Polarization::Horizontal,
+                    Polarization::Horizontal => Polarization::Diagonal,
+                    Polarization::Diagonal => Polarization::Vertical,
+                    Polarization::Vertical => Polarization::Circular,
+                    Polarization::Circular => Polarization::Unpolarized,
+                }
+            }
+            SlitControl::Analyzer => self.analyzer = next_polarizer(self.analyzer),
+            SlitControl::Eraser => {
+                if self.is_eraser() {
+                    self.polarizers = [None; MAX_SLIT_COUNT as usize];
+                    self.analyzer = None;
+                } else if self.slit_count >= 2 {
+                    // crossed polarizers over the first two slits, lit at 45 degrees so
+                    // both get the same amount. the analyzer is left to bring the fringes back
+                    self.polarization = Polarization::Diagonal;
+                    self.polarizers = [None; MAX_SLIT_COUNT as usize];
+                    self.polarizers[0] = Some(0.);
+                    self.polarizers[1] = Some(90.);
+                }
+            }
+            SlitControl::Plate => {
+                self.plate = match self.plate {
+                    Plate::None => Plate::Phase,
+                    Plate::Phase => Plate::Glass,
+                    Plate::Glass => Plate::None,
+                }
+            }
+            SlitControl::PlateSlit => {
+                let new = self.plate_slit as f32 + val;
+                if (0. ..self.slit_count as f32).contains(&new) {
+                    self.plate_slit = new as u32;
+                }
+            }
+            SlitControl::PlatePhase => {
+                let new = self.plate_phase + val;
+                if (0. ..=360.).contains(&new) {
+                    self.plate_phase += val
+                }
+            }
+            SlitControl::PlateThickness => {
+                let new = self.plate_thickness + Length::micrometers(val);
+                if (Length::micrometers(0.)..=Length::micrometers(20.)).contains(&new) {
+                    self.plate_thickness = new
+                }
+            }
+            SlitControl::PlateIndex => {
+                let new = ((self.plate_index + val) * 100.).round() / 100.;
+                if (1. ..=2.).contains(&new) {
+                    self.plate_index = new
+                }
+            }
+            SlitControl::Coherence => {
+                self.coherence = match self.coherence {
+                    Coherence::Coherent => Coherence::Incoherent,
+                    Coherence::Incoherent => Coherence::Coherent,
+                }
+            }
+            SlitControl::Source => {
+                let old_unit = slit_unit(self);
+                self.source = match self.source {
+                    Source::Laser => Source::Sodium,
+                    Source::Sodium => Source::Mercury,
+                    Source::Mercury => Source::White,
+                    Source::White => Source::Incandescent,
+                    Source::Incandescent => Source::Electron,
+                    Source::Electron => Source::Neutron,
+                    Source::Neutron => Source::Helium,
+                    Source::Helium => Source::C60,
+                    Source::C60 => Source::Laser,
+                };
+                // same numbers in the new units, so 50 micrometers of light becomes
+                // 50 nanometers of matter wave and the picture stays put
+                let ratio = slit_unit(self) / old_unit;
+                self.separation = self.separation * ratio;
+                self.slit_width = self.slit_width * ratio;
+
+                // start each species off at its usual speed
+                if let Some(species) = species(&self.source) {
+                    if species.charged {
+                        self.voltage = species.usual_speed;
+                    } else {
+                        self.velocity = species.usual_speed;
+                    }
+                }
+            }
+            SlitControl::Voltage => {
+                let new = self.voltage + val;
+                if (5. ..=300.).contains(&new) {
+                    self.voltage += val
+                }
+            }
+            SlitControl::Velocity => {
+                let new = self.velocity + val;
+                if (100. ..=5000.).contains(&new) {
+                    self.velocity += val
+                }
+            }
+            SlitControl::SourceWidth => {
+                let new = self.source_width + Length::micrometers(val);
+                if (Length::micrometers(0.)..=Length::micrometers(2000.)).contains(&new) {
+                    self.source_width = new
+                }
+            }
+            SlitControl::SourceDistance => {
+                let new = self.source_distance + Distance::centimeters(val);
+                if (Distance::centimeters(5.)..=Distance::centimeters(100.)).contains(&new) {
+                    self.source_distance = new
+                }
+            }
+            SlitControl::WhichPath => {
+                // steps of 0.1 don't add up exactly, and we want to land on 1
+                let new = ((self.which_path + val) * 10.).round() / 10.;
+                if (0. ..=1.).contains(&new) {
+                    self.which_path = new
                 }
             }
+            SlitControl::TagSlits => self.tag_slits = !self.tag_slits,
+            SlitControl::Seed => self.seed = self.seed.saturating_add_signed(val as i64),
             SlitControl::ScreenDistance => {
-                let new = self.screen_distance + val;
-                if new >= 20. && new <= 200. {
-                    self.screen_distance += val
+                let new = self.screen_distance + Distance::centimeters(val);
+                if (Distance::centimeters(20.)..=Distance::centimeters(200.)).contains(&new) {
+                    self.screen_distance = new
                 }
             }
             SlitControl::Wavelength => {
-                let new = self.wavelength + val;
-                if new <= MAX_WAVELENGTH && new >= MIN_WAVELENGTH {
-                    self.wavelength += val
+                let new = self.wavelength + Wavelength::nanometers(val);
+                if (MIN_WAVELENGTH..=MAX_WAVELENGTH).contains(&new) {
+                    self.wavelength = new
                 }
             }
             SlitControl::Width => {
-                let new = self.slit_width + val;
-                if new >= 1. && new <= 15. {
-                    self.slit_width += val
+                let unit = slit_unit(self);
+                let new = self.slit_width + unit * val;
+                if (unit * 1. ..=unit * 15.).contains(&new) {
+                    self.slit_width = new
+                }
+            }
+            SlitControl::Diffraction => {
+                self.diffraction = match self.diffraction {
+                    Diffraction::Auto => Diffraction::Fraunhofer,
+                    Diffraction::Fraunhofer => Diffraction::Fresnel,
+                    Diffraction::Fresnel => Diffraction::Auto,
                 }
             }
             SlitControl::Input => {
                 self.toggle_input = match self.toggle_input {
                     InputType::Light => InputType::Particles,
-                    InputType::Particles => InputType::Light,
+                    InputType::Particles => InputType::Wave,
+                    InputType::Wave => InputType::Light,
                 }
             }
         }
     }
 }
 
+// off, then round the usual angles
+fn next_polarizer(polarizer: Option<f32>) -> Option<f32> {
+    match polarizer {
+        None => Some(0.),
+        Some(angle) if angle < 135. => Some(angle + 45.),
+        Some(_) => None,
+    }
+}
+
+// far field of the current aperture mask, None for plain slits.
+// rebuilt whenever SlitStructure changes since the ffts aren't free
+#[derive(Resource, Default)]
+pub struct ApertureFarField(pub Option<FarField>);
+
+// the wave view's grid, None unless it's showing
+#[derive(Resource, Default)]
+pub struct WaveSimulation(pub Option<WaveField>);
+
+// where particles can land, rebuilt whenever SlitStructure changes in particle mode.
+// None until then
+#[derive(Resource, Default)]
+pub struct ParticleSampler(pub Option<PatternSampler>);
+
+// how finely ParticleTheory splits up the screen
+pub const THEORY_BINS: usize = 1000;
+
+// the share of the particles theory puts in each of THEORY_BINS bins across the screen,
+// worked out with ParticleSampler but from the intensity itself, see
+// physics::histogram::expected_shares. empty until then
+#[derive(Resource, Default)]
+pub struct ParticleTheory(pub Vec<f32>);
+
+// the wave steering the particles for ParticleView::Paths, one per line of
+// ParticleSampler's spectrum. empty for masks, see physics::bohm
+#[derive(Resource, Default)]
+pub struct GuidingWaves(pub Vec<Option<GuidingWave>>);
+
+// every random choice in particle mode comes from here. reseeded with
+// SlitStructure::seed whenever the setup changes, see reset_particles
 #[derive(Resource)]
-pub struct ParticleTimer(pub Timer);
+pub struct ParticleRng(pub StdRng);
+
+impl Default for ParticleRng {
+    fn default() -> Self {
+        Self(StdRng::seed_from_u64(0))
+    }
+}
+
+// the rates the emission rate button steps through, particles per second.
+// slow enough to watch them land one at a time, fast enough to build up the pattern
+pub const EMISSION_RATES: [f32; 14] = [
+    0.2, 0.5, 1., 2., 5., 10., 20., 50., 100., 200., 500., 1000., 2000., 5000.,
+];
+
+// how many of the latest particles show the path they took, see ParticleView::Paths
+pub const RECENT_PATHS: usize = 12;
+
+// how many particles the screen holds on to, see ParticlesMesh
+pub const PARTICLE_LIMITS: [usize; 8] = [1000, 2000, 5000, 10000, 20000, 50000, 100000, 200000];
+
+// the density grid gets 2 pixels a cell on the screen, see ParticlesMesh
+pub const DENSITY_COLUMNS: usize = 250;
+pub const DENSITY_ROWS: usize = 50;
+
+// what the particle screen shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParticleView {
+    // every kept particle as a dot of its own color
+    Points,
+    // every hit since the last reset, counted up per cell and shaded
+    Density(DensityScale),
+    // side on, the de broglie-bohm paths from the slits up to the screen
+    Paths,
+}
+
+impl std::fmt::Display for ParticleView {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParticleView::Points => write!(f, "Points"),
+            ParticleView::Density(scale) => write!(f, "{:?} Density", scale),
+            ParticleView::Paths => write!(f, "Bohm Paths"),
+        }
+    }
+}
+
+// how fast particle mode fires and how it shows what landed. kept out of
+// SlitStructure so changing it doesn't throw away the particles already on the screen
+#[derive(Resource, Debug)]
+pub struct ParticleEmitter {
+    pub rate: usize, // index into EMISSION_RATES
+    pub kept: usize, // index into PARTICLE_LIMITS
+    pub view: ParticleView,
+    pub paused: bool,
+    pub queued: u32, // fired by hand, go out even when paused
+    owed: f32,       // the fraction of a particle carried over from last step
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        ParticleEmitter {
+            rate: 12, // 2000 a second
+            kept: 5,  // 50000 on screen at once
+            view: ParticleView::Points,
+            paused: false,
+            queued: 0,
+            owed: 0.,
+        }
+    }
+}
+
+impl ParticleEmitter {
+    pub fn per_second(&self) -> f32 {
+        EMISSION_RATES[self.rate]
+    }
+
+    pub fn limit(&self) -> usize {
+        PARTICLE_LIMITS[self.kept]
+    }
 
-impl Default for ParticleTimer {
+    /// how many particles to fire for `seconds` more of running
+    pub fn due(&mut self, seconds: f32) -> u32 {
+        let mut count = std::mem::take(&mut self.queued);
+        if !self.paused {
+            self.owed += self.per_second() * seconds;
+            let whole = self.owed.floor();
+            self.owed -= whole;
+            count += whole as u32;
+        }
+        count
+    }
+
+    pub fn add_val(&mut self, opt: &EmitterControl, val: f32) {
+        match opt {
+            EmitterControl::Rate => {
+                if let Some(new) = self.rate.checked_add_signed(val as isize) {
+                    self.rate = new.min(EMISSION_RATES.len() - 1)
+                }
+            }
+            EmitterControl::Kept => {
+                if let Some(new) = self.kept.checked_add_signed(val as isize) {
+                    self.kept = new.min(PARTICLE_LIMITS.len() - 1)
+                }
+            }
+            EmitterControl::View => {
+                self.view = match self.view {
+                    ParticleView::Points => ParticleView::Density(DensityScale::Linear),
+                    ParticleView::Density(DensityScale::Linear) => {
+                        ParticleView::Density(DensityScale::Sqrt)
+                    }
+                    ParticleView::Density(DensityScale::Sqrt) => {
+                        ParticleView::Density(DensityScale::Log)
+                    }
+                    ParticleView::Density(DensityScale::Log) => ParticleView::Paths,
+                    ParticleView::Paths => ParticleView::Points,
+                }
+            }
+            EmitterControl::Pause => {
+                self.paused = !self.paused;
+                // don't let a backlog out all at once on resume
+                self.owed = 0.;
+            }
+            EmitterControl::FireOne => self.queued += 1,
+        }
+    }
+}
+
+// what the camera at the screen can be set to, see physics::detector
+pub const QUANTUM_EFFICIENCIES: [f32; 9] = [0.05, 0.1, 0.2, 0.3, 0.5, 0.7, 0.8, 0.9, 1.];
+pub const DARK_RATES: [f32; 10] = [0., 1., 2., 5., 10., 20., 50., 100., 200., 500.];
+// as a share of the screen's width
+pub const BLURS: [f32; 7] = [0., 0.001, 0.002, 0.005, 0.01, 0.02, 0.05];
+pub const PIXEL_COUNTS: [Option<usize>; 7] = [
+    None,
+    Some(25),
+    Some(50),
+    Some(100),
+    Some(200),
+    Some(500),
+    Some(1000),
+];
+
+// the camera's settings, each an index into the lists above. like ParticleEmitter
+// it stays out of SlitStructure, a new setting only changes the hits still to come
+#[derive(Resource, Debug)]
+pub struct ParticleDetector {
+    pub efficiency: usize,
+    pub dark_rate: usize,
+    pub blur: usize,
+    pub pixels: usize,
+}
+
+impl Default for ParticleDetector {
+    // a perfect one to start with
     fn default() -> Self {
-        Self(Timer::from_seconds(0.005, TimerMode::Repeating))
+        ParticleDetector {
+            efficiency: QUANTUM_EFFICIENCIES.len() - 1,
+            dark_rate: 0,
+            blur: 0,
+            pixels: 0,
+        }
     }
 }
 
+impl ParticleDetector {
+    pub fn model(&self) -> Detector {
+        Detector {
+            efficiency: QUANTUM_EFFICIENCIES[self.efficiency],
+            dark_rate: DARK_RATES[self.dark_rate],
+            blur: BLURS[self.blur],
+            pixels: PIXEL_COUNTS[self.pixels],
+        }
+    }
+
+    pub fn add_val(&mut self, opt: &DetectorControl, val: f32) {
+        let (index, len) = match opt {
+            DetectorControl::Efficiency => (&mut self.efficiency, QUANTUM_EFFICIENCIES.len()),
+            DetectorControl::DarkRate => (&mut self.dark_rate, DARK_RATES.len()),
+            DetectorControl::Blur => (&mut self.blur, BLURS.len()),
+            DetectorControl::Pixels => (&mut self.pixels, PIXEL_COUNTS.len()),
+        };
+        if let Some(new) = index.checked_add_signed(val as isize) {
+            *index = new.min(len - 1)
+        }
+    }
+}
+
+// asks export_data to write the hits and the profile out, and what came of the last time:
+// the stamp in the files' names, or why they couldn't be written
+#[derive(Resource, Default)]
+pub struct DataExport {
+    pub requested: bool,
+    pub last: Option<std::io::Result<String>>,
+}
+
 // UI
 
 #[derive(Component, Copy, Clone)]
 pub enum SlitControl {
+    Aperture,
     Separation,
+    SlitCount,
+    SelectedSlit,
+    SelectedWidth,
+    SelectedTransmission,
+    SelectedPolarizer,
+    Polarization,
+    Analyzer,
+    Eraser,
+    Plate,
+    PlateSlit,
+    PlatePhase,
+    PlateThickness,
+    PlateIndex,
+    Coherence,
+    WhichPath,
+    TagSlits,
+    Seed,
     Width,
+    Source,
     Wavelength,
+    Voltage,
+    Velocity,
+    SourceWidth,
+    SourceDistance,
     ScreenDistance,
+    Diffraction,
     Input,
 }
 
+// the detector's buttons, see ParticleDetector
+#[derive(Component, Copy, Clone)]
+pub enum DetectorControl {
+    Efficiency,
+    DarkRate,
+    Blur,
+    Pixels,
+}
+
+// the particle emitter's buttons, see ParticleEmitter
+#[derive(Component, Copy, Clone)]
+pub enum EmitterControl {
+    Rate,
+    Kept,
+    View,
+    Pause,
+    FireOne,
+}
+
+// the two cursors for measuring fringes on the screen, see interference::ruler
+#[derive(Resource, Debug)]
+pub struct Ruler {
+    pub cursors: [f32; 2],       // across the screen, 0 to 1
+    pub dragging: Option<usize>, // which cursor the mouse has hold of
+}
+
+impl Default for Ruler {
+    fn default() -> Self {
+        Ruler {
+            cursors: [0.4, 0.6],
+            dragging: None,
+        }
+    }
+}
+
+// one of the ruler's cursors, indexes into Ruler::cursors
+#[derive(Component)]
+pub struct RulerCursor(pub usize);
+
+// what the ruler measures
 #[derive(Component)]
-pub enum Slit {
-    LeftSlit,
-    RightSlit,
+pub struct RulerLabel;
+
+// the ways of showing the particles, only the one ParticleView picks is visible
+#[derive(Component, Clone, Copy)]
+pub enum ParticleLayer {
+    Points,
+    Heatmap,
+    Paths,
 }
 
+impl ParticleLayer {
+    pub fn shown_in(&self, view: ParticleView) -> bool {
+        matches!(
+            (self, view),
+            (ParticleLayer::Points, ParticleView::Points)
+                | (ParticleLayer::Heatmap, ParticleView::Density(_))
+                | (ParticleLayer::Paths, ParticleView::Paths)
+        )
+    }
+}
+
+// the faint spread of paths the whole ensemble takes, and the bright ones of the
+// latest particles over it, see interference::paths
+#[derive(Component)]
+pub struct EnsemblePaths;
+
+#[derive(Component)]
+pub struct ArrivalPaths;
+
+// the parts of the hit histogram that change, see interference::histogram
+#[derive(Component)]
+pub struct HistogramBars;
+
+#[derive(Component)]
+pub struct HistogramCurve;
+
+#[derive(Component)]
+pub struct HistogramLabel;
+
+// the button that sets DataExport going
+#[derive(Component, Copy, Clone)]
+pub struct ExportButton;
+
+// which slit this is, counting from the left
+#[derive(Component)]
+pub struct Slit(pub u32);
+
 #[derive(Component)]
 pub struct SlitScreen;
 
+// drawn in front of whichever slit has the plate
+#[derive(Component)]
+pub struct PlateMarker;
+
 #[derive(Component)]
 pub struct Light;
 
@@ -122,46 +835,259 @@ pub struct DisplayInfo;
 #[derive(Component)]
 pub struct Increment(pub f32);
 
+// which page of controls is showing, indexes into ui::PAGE_NAMES
+#[derive(Resource, Default)]
+pub struct CurrentPage(pub usize);
+
+#[derive(Component)]
+pub struct ControlPage(pub usize);
+
+// steps through the pages, -1 back and 1 forward
+#[derive(Component)]
+pub struct PageButton(pub i32);
+
+#[derive(Component)]
+pub struct PageTitle;
+
 // SHADERS
+// every particle gets the color of its own wavelength,
+// or of its slit if the which path detector caught it.
+// only the newest `capacity` are kept, once it's full each new one takes the oldest's
+// place. the mesh on screen is made once and then just gets what changed, see upload_particles.
+// `density` counts every hit regardless, for ParticleView::Density
 #[derive(Resource, Debug)]
-pub struct ParticlesMesh(pub Vec<[f32; 3]>);
+pub struct ParticlesMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub colors: Vec<[f32; 4]>,
+    pub tags: Vec<Option<u32>>, // which slit, None if it went through unseen
+    pub times: Vec<f32>,        // when each one landed, see `clock`
+    pub density: DensityGrid,
+    pub recent: VecDeque<Hit>, // the last RECENT_PATHS, newest at the back
+    pub handle: Option<Handle<Mesh>>, // weak, the entity on screen holds the mesh
+    pub heatmap: Option<Handle<Image>>, // the density's picture, weak too
+    capacity: usize,
+    clock: f32,      // seconds since the screen was cleared
+    oldest: usize,   // where the next one goes once it's full
+    unsynced: usize, // how many were added since the mesh on screen was brought up to date
+    rebuild: bool,   // everything moved around, the mesh has to start over
+}
 
 impl ParticlesMesh {
-    pub fn add_particle(&mut self, coords: [f32; 3]) {
-        self.0.push(coords);
-        // println!("{:#?}", self.0);
+    pub fn add_particle(&mut self, coords: [f32; 3], color: Color, tag: Option<u32>) {
+        if self.positions.len() < self.capacity {
+            self.positions.push(coords);
+            self.colors.push(color.as_rgba_f32());
+            self.tags.push(tag);
+            self.times.push(self.clock);
+        } else {
+            self.positions[self.oldest] = coords;
+            self.colors[self.oldest] = color.as_rgba_f32();
+            self.tags[self.oldest] = tag;
+            self.times[self.oldest] = self.clock;
+            self.oldest = (self.oldest + 1) % self.capacity;
+        }
+        self.unsynced = (self.unsynced + 1).min(self.capacity);
+    }
+
+    /// moves the clock the hits are timed by on `seconds`
+    pub fn tick(&mut self, seconds: f32) {
+        self.clock += seconds;
+    }
+
+    /// the hits kept, oldest first
+    pub fn arrivals(&self) -> Vec<Arrival> {
+        let len = self.positions.len();
+        let first = if len == self.capacity { self.oldest } else { 0 };
+        // particle x runs from -249 to 249 and y from -49 to 49, see add_particle
+        (0..len)
+            .map(|k| (first + k) % len)
+            .map(|i| Arrival {
+                time: self.times[i],
+                x: (self.positions[i][0] + 249.) / 498.,
+                y: (self.positions[i][1] + 49.) / 98.,
+                tag: self.tags[i],
+            })
+            .collect()
+    }
+
+    /// keeps `hit` among the recent ones, pushing the oldest out
+    pub fn remember(&mut self, hit: Hit) {
+        if self.recent.len() == RECENT_PATHS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(hit);
     }
 
     pub fn reset_mesh(&mut self) {
-        self.0 = vec![];
+        self.positions = vec![];
+        self.colors = vec![];
+        self.tags = vec![];
+        self.times = vec![];
+        self.clock = 0.;
+        self.density.clear();
+        self.recent.clear();
+        self.handle = None;
+        self.heatmap = None;
+        self.oldest = 0;
+        self.unsynced = 0;
+        self.rebuild = true;
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// keeps at most `capacity` particles from now on, dropping the oldest if there's too many
+    pub fn set_capacity(&mut self, capacity: usize) {
+        if capacity == self.capacity {
+            return;
+        }
+        // put them back in the order they came in
+        if self.positions.len() == self.capacity {
+            self.positions.rotate_left(self.oldest);
+            self.colors.rotate_left(self.oldest);
+            self.tags.rotate_left(self.oldest);
+            self.times.rotate_left(self.oldest);
+        }
+        let extra = self.positions.len().saturating_sub(capacity);
+        self.positions.drain(..extra);
+        self.colors.drain(..extra);
+        self.tags.drain(..extra);
+        self.times.drain(..extra);
+
+        self.capacity = capacity;
+        self.oldest = 0;
+        self.unsynced = 0;
+        self.rebuild = true;
+    }
+
+    /// the slots written since the last call, in the order they were written,
+    /// or None if they all moved and the mesh should be made again from scratch
+    pub fn take_changes(&mut self) -> Option<Vec<usize>> {
+        let unsynced = std::mem::take(&mut self.unsynced);
+        if std::mem::take(&mut self.rebuild) {
+            return None;
+        }
+        // the slot the next one goes in, the changes are the ones just before it
+        let len = self.positions.len();
+        let next = if len < self.capacity {
+            len
+        } else {
+            self.oldest
+        };
+        Some(
+            (0..unsynced)
+                .map(|k| (next + len - unsynced + k) % len)
+                .collect(),
+        )
     }
 }
 
 impl Default for ParticlesMesh {
     fn default() -> Self {
-        ParticlesMesh(vec![])
+        ParticlesMesh {
+            positions: vec![],
+            colors: vec![],
+            tags: vec![],
+            times: vec![],
+            density: DensityGrid::new(DENSITY_COLUMNS, DENSITY_ROWS),
+            recent: VecDeque::new(),
+            handle: None,
+            heatmap: None,
+            capacity: PARTICLE_LIMITS[ParticleEmitter::default().kept],
+            clock: 0.,
+            oldest: 0,
+            unsynced: 0,
+            rebuild: false,
+        }
     }
 }
 
 #[derive(AsBindGroup, Debug, TypeUuid, Clone)]
 #[uuid = "f690fdae-d598-45ab-8225-97e2a3f056e0"]
 pub struct LightMaterial {
+    // light over the screen, one physics::intensity_table per line of the spectrum
+    // added up. rgb is the color of the light already scaled by its brightness,
+    // alpha is just the brightness
+    #[texture(0)]
+    #[sampler(1)]
+    pub intensity: Handle<Image>,
     // have to be vec4s so they're all padded correctly or wasm won't compile
-    #[uniform(0)]
-    pub separation: Vec4,
-    #[uniform(1)]
-    pub slit_width: Vec4,
     #[uniform(2)]
-    pub wavelength: Vec4,
-    #[uniform(3)]
-    pub screen_distance: Vec4,
-    //
-    #[uniform(4)]
     pub background_color: Color,
-    #[uniform(5)]
-    pub light_color: Color,
-    #[uniform(6)]
+    #[uniform(3)]
     pub border_color: Color,
+    // x is where the fringe that was straight ahead is now on the screen (0 to 1),
+    // y is 1 if there's a plate in at all
+    #[uniform(4)]
+    pub plate: Vec4,
+}
+
+impl LightMaterial {
+    pub fn new(
+        slit: &SlitStructure,
+        far_field: Option<&FarField>,
+        images: &mut Assets<Image>,
+        background_color: Color,
+        border_color: Color,
+    ) -> Self {
+        // each wavelength makes its own pattern, and they pile up on the screen
+        // in their own colors. the weights add up to 1 so nothing ends up over 1
+        let mut columns = 0;
+        let mut rows = 0;
+        let mut light: Vec<[f32; 4]> = vec![];
+        for line in spectrum(slit) {
+            let table = intensity_table(slit, far_field, line.wavelength);
+            let color = wavelength_to_rgb(&display_wavelength(slit, line.wavelength));
+
+            columns = table.columns;
+            rows = table.rows;
+            light.resize(table.values.len(), [0.; 4]);
+
+            for (total, val) in light.iter_mut().zip(table.values) {
+                let val = val.clamp(0., 1.) * line.weight;
+                total[0] += color.r() * val;
+                total[1] += color.g() * val;
+                total[2] += color.b() * val;
+                total[3] += val;
+            }
+        }
+
+        // masks don't know about the plate
+        let plate = match (&slit.plate, far_field) {
+            (Plate::None, _) | (_, Some(_)) => Vec4::ZERO,
+            _ => {
+                let shift: f32 = spectrum(slit)
+                    .iter()
+                    .map(|line| fringe_shift(slit, line.wavelength) * line.weight)
+                    .sum();
+                Vec4::new(0.5 + shift, 1., 0., 0.)
+            }
+        };
+
+        let data = light
+            .iter()
+            .flat_map(|total| total.map(|val| (val.clamp(0., 1.) * 255.) as u8))
+            .collect();
+
+        let intensity = images.add(Image::new(
+            Extent3d {
+                width: columns as u32,
+                height: rows as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8Unorm,
+        ));
+
+        LightMaterial {
+            intensity,
+            background_color,
+            border_color,
+            plate,
+        }
+    }
 }
 
 impl Material2d for LightMaterial {
@@ -229,10 +1155,8 @@ impl Material2d for ScreenMaterial {
 
 #[derive(AsBindGroup, Debug, TypeUuid, Clone)]
 #[uuid = "ff3c172b-415f-41f4-811d-aaca03c5d103"]
-pub struct ParticlesMaterial {
-    #[uniform(0)]
-    pub color: Color,
-}
+// the color of each particle comes in with the mesh, see ParticlesMesh
+pub struct ParticlesMaterial {}
 
 impl Material2d for ParticlesMaterial {
     fn vertex_shader() -> ShaderRef {
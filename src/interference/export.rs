@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::component::{
+    ApertureFarField, DataExport, ParticleDetector, ParticlesMesh, SlitStructure,
+};
+use crate::physics::export::{hits_csv, profile_csv};
+
+// writes the hits kept on screen and the profile the current setup predicts into the
+// working directory, double_slit_hits_<stamp>.csv and double_slit_profile_<stamp>.csv.
+// see unused_stamp for why one export never writes over another
+pub fn export_data(
+    mut export: ResMut<DataExport>,
+    particles_mesh: Res<ParticlesMesh>,
+    slit_structure: Res<SlitStructure>,
+    far_field: Res<ApertureFarField>,
+    detector: Res<ParticleDetector>,
+) {
+    let detector = detector.model();
+    let stamp = unused_stamp();
+    let hits = hits_csv(&slit_structure, &detector, &particles_mesh.arrivals());
+    let profile = profile_csv(&slit_structure, &detector, far_field.0.as_ref());
+
+    let written = fs::write(file_name("hits", &stamp), hits)
+        .and_then(|_| fs::write(file_name("profile", &stamp), profile));
+    if let Err(err) = &written {
+        error!("couldn't export to csv: {}", err);
+    }
+
+    export.requested = false;
+    export.last = Some(written.map(|_| stamp));
+}
+
+pub fn export_data_criteria(export: Res<DataExport>) -> bool {
+    export.requested
+}
+
+fn file_name(kind: &str, stamp: &str) -> String {
+    format!("double_slit_{}_{}.csv", kind, stamp)
+}
+
+// the unix time in milliseconds, with a count after it if that's somehow taken already
+fn unused_stamp() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    (0..)
+        .map(|count| match count {
+            0 => millis.to_string(),
+            _ => format!("{}_{}", millis, count),
+        })
+        .find(|stamp| {
+            ["hits", "profile"]
+                .iter()
+                .all(|kind| !Path::new(&file_name(kind, stamp)).exists())
+        })
+        .unwrap()
+}
@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::PrimitiveTopology::{LineStrip, TriangleList};
+use bevy::sprite::{Anchor, MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::component::{
+    HistogramBars, HistogramCurve, HistogramLabel, InputType, ParticleDetector, ParticleEmitter,
+    ParticleTheory, ParticlesMesh, SlitStructure,
+};
+use crate::physics::histogram::{bin_counts, chi_square};
+use crate::ui::CONTROL_BACKGROUND;
+
+use super::{flat_mesh, BASELINE_X_SLITS, PLOT_HEIGHT, PLOT_Y, SCREEN_COLOR, SLIT_SCREEN_WIDTH};
+
+// 5 pixels a bin across the screen
+const HISTOGRAM_BINS: usize = 100;
+
+const BAR_COLOR: Color = Color::rgb(0.9, 0.6, 0.3);
+const CURVE_COLOR: Color = Color::WHITE;
+
+// the hits counted up along x under the particle screen, with what the pattern
+// says the counts should be drawn over them. put up empty along with the particles,
+// update_histogram fills it in, and output_particles despawns it with the rest of them
+pub fn spawn_histogram(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    asset_server: &AssetServer,
+) {
+    let at = |z: f32| Transform::from_translation(Vec3::new(BASELINE_X_SLITS, PLOT_Y, z));
+
+    // backdrop
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::Box::new(SLIT_SCREEN_WIDTH, PLOT_HEIGHT, 0.).into())
+                .into(),
+            material: materials.add(ColorMaterial::from(SCREEN_COLOR)),
+            transform: at(0.),
+            ..default()
+        })
+        .insert(InputType::Particles);
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(flat_mesh(TriangleList, vec![])).into(),
+            material: materials.add(ColorMaterial::from(BAR_COLOR)),
+            transform: at(0.1),
+            ..default()
+        })
+        .insert(HistogramBars)
+        .insert(InputType::Particles);
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(flat_mesh(LineStrip, vec![])).into(),
+            material: materials.add(ColorMaterial::from(CURVE_COLOR)),
+            transform: at(0.2),
+            ..default()
+        })
+        .insert(HistogramCurve)
+        .insert(InputType::Particles);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server
+                        .load("fonts/BigBlue TerminalPlus Nerd Font Complete Mono.TTF"),
+                    font_size: 8.0,
+                    color: CONTROL_BACKGROUND,
+                },
+            ),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform::from_translation(Vec3::new(
+                BASELINE_X_SLITS - SLIT_SCREEN_WIDTH / 2.,
+                PLOT_Y - PLOT_HEIGHT / 2. - 2.,
+                0.,
+            )),
+            ..default()
+        })
+        .insert(HistogramLabel)
+        .insert(InputType::Particles);
+}
+
+// recounts the hits kept on screen and swaps the new shapes into the meshes spawn_histogram made
+#[allow(clippy::too_many_arguments)]
+pub fn update_histogram(
+    mut meshes: ResMut<Assets<Mesh>>,
+    particles_mesh: Res<ParticlesMesh>,
+    theory: Res<ParticleTheory>,
+    detector: Res<ParticleDetector>,
+    emitter: Res<ParticleEmitter>,
+    bars_query: Query<&Mesh2dHandle, With<HistogramBars>>,
+    curve_query: Query<&Mesh2dHandle, With<HistogramCurve>>,
+    mut label_query: Query<&mut Text, With<HistogramLabel>>,
+) {
+    // particle x runs from -249 to 249, see add_particle
+    let counts = bin_counts(
+        particles_mesh
+            .positions
+            .iter()
+            .map(|position| (position[0] + 249.) / 498.),
+        HISTOGRAM_BINS,
+    );
+    let hits = particles_mesh.positions.len() as f32;
+    // what the camera should record, not just where the particles land
+    let detector = detector.model();
+    let shares = (!theory.0.is_empty()).then(|| {
+        detector.smear(
+            &theory.0,
+            HISTOGRAM_BINS,
+            detector.dark_share(emitter.per_second()),
+        )
+    });
+    // with nothing on the screen yet, show the shape of what's coming
+    let expected: Option<Vec<f32>> = shares.map(|shares| {
+        if hits > 0. {
+            shares.iter().map(|share| share * hits).collect()
+        } else {
+            shares
+        }
+    });
+
+    let peak = counts
+        .iter()
+        .chain(expected.iter().flatten())
+        .fold(0_f32, |peak, value| peak.max(*value));
+    let height = |value: f32| {
+        let fraction = if peak > 0. { value / peak } else { 0. };
+        (fraction - 0.5) * PLOT_HEIGHT
+    };
+    let bin_width = SLIT_SCREEN_WIDTH / HISTOGRAM_BINS as f32;
+    let left = -SLIT_SCREEN_WIDTH / 2.;
+
+    // bars, two triangles each
+    let bars: Vec<[f32; 3]> = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0.)
+        .flat_map(|(i, count)| {
+            let (x0, x1) = (
+                left + i as f32 * bin_width,
+                left + (i + 1) as f32 * bin_width,
+            );
+            let (y0, y1) = (-PLOT_HEIGHT / 2., height(*count));
+            [
+                [x0, y0, 0.],
+                [x1, y0, 0.],
+                [x1, y1, 0.],
+                [x0, y0, 0.],
+                [x1, y1, 0.],
+                [x0, y1, 0.],
+            ]
+        })
+        .collect();
+    for handle in bars_query.iter() {
+        if let Some(mesh) = meshes.get_mut(&handle.0) {
+            *mesh = flat_mesh(TriangleList, bars.clone());
+        }
+    }
+
+    // theory, through the middle of each bin
+    let curve: Vec<[f32; 3]> = expected
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, value)| [left + (i as f32 + 0.5) * bin_width, height(*value), 0.])
+        .collect();
+    for handle in curve_query.iter() {
+        if let Some(mesh) = meshes.get_mut(&handle.0) {
+            *mesh = flat_mesh(LineStrip, curve.clone());
+        }
+    }
+
+    let fit = match expected
+        .as_ref()
+        .filter(|_| hits > 0.)
+        .and_then(|expected| chi_square(&counts, expected))
+    {
+        Some((value, freedom)) => {
+            format!("chi^2/dof {:.2} ({} dof)", value / freedom as f32, freedom)
+        }
+        None => "chi^2/dof: need more hits".to_string(),
+    };
+    for mut text in label_query.iter_mut() {
+        // the density grid keeps counting after the oldest particles are dropped
+        text.sections[0].value = format!(
+            "{} hits kept of {}   {}",
+            hits,
+            particles_mesh.density.total(),
+            fit
+        );
+    }
+}
+
+// new hits, a new pattern or detector, or a histogram that was just put up and is still empty
+pub fn update_histogram_criteria(
+    slit_structure: Res<SlitStructure>,
+    particles_mesh: Res<ParticlesMesh>,
+    theory: Res<ParticleTheory>,
+    detector: Res<ParticleDetector>,
+    new_labels: Query<(), Added<HistogramLabel>>,
+) -> bool {
+    matches!(slit_structure.toggle_input, InputType::Particles)
+        && (particles_mesh.is_changed()
+            || theory.is_changed()
+            || detector.is_changed()
+            || !new_labels.is_empty())
+}
@@ -1,19 +1,24 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
 use crate::{
-    component::{InputType, LightMaterial, ParticlesMesh, SlitStructure},
-    slit::wavelength_to_rgb,
+    component::{
+        ApertureFarField, InputType, LightMaterial, ParticlesMesh, SlitStructure, WaveSimulation,
+    },
     WINDOW_HEIGHT,
 };
 
 use super::{BASELINE_X_SLITS, BORDER_COLOR, SCREEN_COLOR, SLIT_SCREEN_HEIGHT, SLIT_SCREEN_WIDTH};
 
+#[allow(clippy::too_many_arguments)]
 pub fn output_light(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut light_material: ResMut<Assets<LightMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     slit_structure: Res<SlitStructure>,
+    far_field: Res<ApertureFarField>,
     mut particles_mesh: ResMut<ParticlesMesh>,
+    mut simulation: ResMut<WaveSimulation>,
     particles_query: Query<Entity, With<InputType>>,
 ) {
     for entity in particles_query.iter() {
@@ -21,6 +26,8 @@ pub fn output_light(
     }
 
     particles_mesh.reset_mesh();
+    // the wave view's grid goes with its picture
+    simulation.0 = None;
 
     let y = (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.;
     commands
@@ -28,15 +35,13 @@ pub fn output_light(
             mesh: meshes
                 .add(shape::Box::new(SLIT_SCREEN_WIDTH, SLIT_SCREEN_HEIGHT, 0.).into())
                 .into(),
-            material: light_material.add(LightMaterial {
-                screen_distance: Vec4::new(slit_structure.screen_distance, 0., 0., 0.),
-                separation: Vec4::new(slit_structure.separation, 0., 0., 0.),
-                slit_width: Vec4::new(slit_structure.slit_width, 0., 0., 0.),
-                wavelength: Vec4::new(slit_structure.wavelength, 0., 0., 0.),
-                background_color: SCREEN_COLOR,
-                light_color: wavelength_to_rgb(&slit_structure.wavelength),
-                border_color: BORDER_COLOR,
-            }),
+            material: light_material.add(LightMaterial::new(
+                &slit_structure,
+                far_field.0.as_ref(),
+                &mut images,
+                SCREEN_COLOR,
+                BORDER_COLOR,
+            )),
             transform: Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, 0.)),
             ..default()
         })
@@ -44,9 +49,5 @@ pub fn output_light(
 }
 
 pub fn light_criteria(slit_structure: Res<SlitStructure>) -> bool {
-    if slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Light) {
-        true
-    } else {
-        false
-    }
+    slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Light)
 }
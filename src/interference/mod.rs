@@ -1,19 +1,38 @@
 use crate::component::{
-    InputType, ParticleTimer, ParticlesMaterial, ParticlesMesh, ScreenMaterial, SlitStructure,
+    ApertureFarField, DataExport, GuidingWaves, InputType, ParticleDetector, ParticleEmitter,
+    ParticleRng, ParticleSampler, ParticleTheory, ParticlesMaterial, ParticlesMesh, Ruler,
+    ScreenMaterial, SlitStructure, WaveSimulation,
 };
-use crate::slit::wavelength_to_rgb;
+use crate::physics::aperture::{mask_for, FarField};
 use crate::{component::LightMaterial, WINDOW_HEIGHT};
+use bevy::render::render_resource::PrimitiveTopology;
 use bevy::sprite::Material2dPlugin;
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
+use self::export::{export_data, export_data_criteria};
+use self::histogram::{update_histogram, update_histogram_criteria};
 use self::light::{light_criteria, output_light};
 use self::particles::{
     add_particle, add_particles_criteria, output_particles, output_particles_criteria,
-    reset_particles, reset_particles_criteria,
+    reset_particles, reset_particles_criteria, show_particle_view, update_sampler,
+    update_sampler_criteria, upload_density, upload_density_criteria, upload_particles,
+    upload_particles_criteria,
 };
+use self::paths::{
+    update_arrivals, update_arrivals_criteria, update_ensemble, update_ensemble_criteria,
+};
+use self::profile::output_profile;
+use self::ruler::{drag_ruler, setup_ruler, update_ruler, update_ruler_criteria};
+use self::wave::{output_wave, step_wave, step_wave_criteria, wave_criteria};
 
+mod export;
+mod histogram;
 mod light;
 mod particles;
+mod paths;
+mod profile;
+mod ruler;
+mod wave;
 pub struct InterferencePlugin;
 
 impl Plugin for InterferencePlugin {
@@ -21,16 +40,83 @@ impl Plugin for InterferencePlugin {
         app.add_plugin(Material2dPlugin::<LightMaterial>::default())
             .add_plugin(Material2dPlugin::<ScreenMaterial>::default())
             .add_plugin(Material2dPlugin::<ParticlesMaterial>::default())
-            .init_resource::<ParticleTimer>()
+            .init_resource::<ParticleEmitter>()
+            .init_resource::<ParticleDetector>()
+            .init_resource::<ParticleSampler>()
+            .init_resource::<ParticleTheory>()
+            .init_resource::<GuidingWaves>()
+            .init_resource::<ParticleRng>()
+            .init_resource::<ApertureFarField>()
+            .init_resource::<WaveSimulation>()
+            .init_resource::<Ruler>()
+            .init_resource::<DataExport>()
+            .add_startup_system(setup_ruler)
+            .add_system(drag_ruler)
+            .add_system(update_ruler.run_if(update_ruler_criteria).after(drag_ruler))
             .add_startup_system(setup_screen.in_base_set(StartupSet::PostStartup))
-            .add_system(output_light.run_if(light_criteria))
-            .add_system(output_particles.run_if(output_particles_criteria))
+            .add_system(update_aperture.run_if(update_aperture_criteria))
+            .add_system(output_light.run_if(light_criteria).after(update_aperture))
+            .add_system(output_profile.run_if(light_criteria).after(output_light))
+            .add_system(
+                output_particles
+                    .run_if(output_particles_criteria)
+                    .after(reset_particles),
+            )
+            .add_system(
+                upload_particles
+                    .run_if(upload_particles_criteria)
+                    .after(output_particles),
+            )
+            .add_system(
+                upload_density
+                    .run_if(upload_density_criteria)
+                    .after(output_particles),
+            )
+            .add_system(
+                update_ensemble
+                    .run_if(update_ensemble_criteria)
+                    .after(output_particles)
+                    .after(update_sampler),
+            )
+            .add_system(
+                update_arrivals
+                    .run_if(update_arrivals_criteria)
+                    .after(output_particles)
+                    .after(update_sampler),
+            )
+            .add_system(
+                show_particle_view
+                    .run_if(resource_changed::<ParticleEmitter>())
+                    .after(output_particles),
+            )
+            .add_system(
+                update_histogram
+                    .run_if(update_histogram_criteria)
+                    .after(upload_particles)
+                    .after(update_sampler),
+            )
+            .add_system(
+                update_sampler
+                    .run_if(update_sampler_criteria)
+                    .after(update_aperture),
+            )
             .add_system(
                 add_particle
                     .run_if(add_particles_criteria)
                     .in_schedule(CoreSchedule::FixedUpdate),
             )
-            .add_system(reset_particles.run_if(reset_particles_criteria));
+            .add_system(reset_particles.run_if(reset_particles_criteria))
+            .add_system(
+                export_data
+                    .run_if(export_data_criteria)
+                    .after(update_aperture),
+            )
+            .add_system(output_wave.run_if(wave_criteria))
+            .add_system(
+                step_wave
+                    .run_if(step_wave_criteria)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
     }
 }
 
@@ -43,11 +129,31 @@ pub const SLIT_SCREEN_HEIGHT: f32 = 100.;
 pub const SCREEN_COLOR: Color = Color::rgba_linear(0.3, 0.1, 0., 1.0);
 pub const BORDER_COLOR: Color = Color::rgba(0.27843, 0.18039, 0.12157, 1.0);
 
+// the plot under the screen, the histogram for particles or the profile for light
+pub const PLOT_HEIGHT: f32 = 50.;
+pub const PLOT_GAP: f32 = 5.;
+pub const PLOT_Y: f32 = (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.
+    - SLIT_SCREEN_HEIGHT / 2.
+    - PLOT_GAP
+    - PLOT_HEIGHT / 2.;
+
+// a mesh of just `positions` for the plots, normals and uvs don't matter for flat colors
+pub fn flat_mesh(topology: PrimitiveTopology, positions: Vec<[f32; 3]>) -> Mesh {
+    let count = positions.len();
+    let mut mesh = Mesh::new(topology);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; count]);
+    mesh
+}
+
 fn setup_screen(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut light_material: ResMut<Assets<LightMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     slit_structure: Res<SlitStructure>,
+    far_field: Res<ApertureFarField>,
 ) {
     let y = (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.;
 
@@ -56,15 +162,13 @@ fn setup_screen(
             mesh: meshes
                 .add(shape::Box::new(SLIT_SCREEN_WIDTH, SLIT_SCREEN_HEIGHT, 0.).into())
                 .into(),
-            material: light_material.add(LightMaterial {
-                screen_distance: Vec4::new(slit_structure.screen_distance, 0., 0., 0.),
-                separation: Vec4::new(slit_structure.separation, 0., 0., 0.),
-                slit_width: Vec4::new(slit_structure.slit_width, 0., 0., 0.),
-                wavelength: Vec4::new(slit_structure.wavelength, 0., 0., 0.),
-                background_color: SCREEN_COLOR,
-                light_color: wavelength_to_rgb(&slit_structure.wavelength),
-                border_color: BORDER_COLOR,
-            }),
+            material: light_material.add(LightMaterial::new(
+                &slit_structure,
+                far_field.0.as_ref(),
+                &mut images,
+                SCREEN_COLOR,
+                BORDER_COLOR,
+            )),
             transform: Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, 0.)),
             ..default()
         })
@@ -73,3 +177,14 @@ fn setup_screen(
     let mesh = ParticlesMesh::default();
     commands.insert_resource(mesh);
 }
+
+pub fn update_aperture(
+    slit_structure: Res<SlitStructure>,
+    mut far_field: ResMut<ApertureFarField>,
+) {
+    far_field.0 = mask_for(&slit_structure).map(|mask| FarField::new(&mask));
+}
+
+pub fn update_aperture_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    slit_structure.is_changed()
+}
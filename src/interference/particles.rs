@@ -1,28 +1,52 @@
 use crate::component::{
-    InputType, ParticleTimer, ParticlesMaterial, ParticlesMesh, ScreenMaterial, SlitStructure,
+    ApertureFarField, GuidingWaves, InputType, ParticleDetector, ParticleEmitter, ParticleLayer,
+    ParticleRng, ParticleSampler, ParticleTheory, ParticleView, ParticlesMaterial, ParticlesMesh,
+    ScreenMaterial, SlitStructure, WaveSimulation, THEORY_BINS,
 };
+use crate::physics::histogram::{expected_shares, DensityGrid, DensityScale};
+use crate::physics::{bohm::GuidingWave, matter::display_wavelength, sampling::PatternSampler};
 use crate::slit::wavelength_to_rgb;
+use crate::ui::TAG_COLORS;
 use crate::WINDOW_HEIGHT;
 use bevy::prelude::*;
-use bevy::render::render_resource::PrimitiveTopology::PointList;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::render_resource::{
+    Extent3d, PrimitiveTopology::PointList, TextureDimension, TextureFormat,
+};
 use bevy::sprite::MaterialMesh2dBundle;
-use bevy::time::Time;
-use rand::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
 
+use super::histogram::spawn_histogram;
+use super::paths::spawn_paths;
 use super::{BASELINE_X_SLITS, BORDER_COLOR, SCREEN_COLOR, SLIT_SCREEN_HEIGHT, SLIT_SCREEN_WIDTH};
 
+// hot cells go from the screen's color through this to white
+const HEAT_COLOR: Color = Color::rgb(0.9, 0.3, 0.1);
+// counts the detector made up, not any particle's color
+const DARK_COUNT_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+
+// the screen, the mesh the particles go in, the density heatmap and the histogram
+// under them. only when the setup changes, after that upload_particles, upload_density
+// and update_histogram keep them current
+#[allow(clippy::too_many_arguments)]
 pub fn output_particles(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
     mut screen_material: ResMut<Assets<ScreenMaterial>>,
     mut particles_material: ResMut<Assets<ParticlesMaterial>>,
-    slit_structure: Res<SlitStructure>,
-    particles_mesh: Res<ParticlesMesh>,
+    mut color_material: ResMut<Assets<ColorMaterial>>,
+    mut particles_mesh: ResMut<ParticlesMesh>,
+    mut simulation: ResMut<WaveSimulation>,
+    emitter: Res<ParticleEmitter>,
     light_query: Query<Entity, With<InputType>>,
+    asset_server: Res<AssetServer>,
 ) {
     for entity in light_query.iter() {
         commands.entity(entity).despawn();
     }
+    // and whatever the wave view left running
+    simulation.0 = None;
     let y = (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.;
 
     // screen
@@ -40,164 +64,343 @@ pub fn output_particles(
         })
         .insert(InputType::Particles);
 
-    let particles_mesh = get_particles_mesh(&particles_mesh, &slit_structure);
     // particles
+    let handle = meshes.add(get_particles_mesh(&particles_mesh));
+    particles_mesh.take_changes();
+    particles_mesh.handle = Some(handle.clone_weak());
     commands
         .spawn(MaterialMesh2dBundle {
-            mesh: meshes.add(particles_mesh.into()).into(),
-            material: particles_material.add(ParticlesMaterial {
-                color: wavelength_to_rgb(&slit_structure.wavelength),
-            }),
+            mesh: handle.into(),
+            material: particles_material.add(ParticlesMaterial {}),
             transform: Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, 0.1)),
+            visibility: shown(ParticleLayer::Points.shown_in(emitter.view)),
             ..default()
         })
+        .insert(ParticleLayer::Points)
         .insert(InputType::Particles);
-}
 
-pub fn output_particles_criteria(
-    slit_structure: Res<SlitStructure>,
-    mesh: Res<ParticlesMesh>,
-) -> bool {
-    if mesh.is_changed() && matches!(slit_structure.toggle_input, InputType::Particles) {
-        true
-    } else {
-        false
+    // density, inside the border like the particles
+    let density = &particles_mesh.density;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: density.columns as u32,
+            height: density.rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    if let ParticleView::Density(scale) = emitter.view {
+        draw_density(density, scale, &mut image.data);
     }
-}
-
-pub fn get_particles_mesh(particles_mesh: &ParticlesMesh, slit: &SlitStructure) -> Mesh {
-    let mut mesh = Mesh::new(PointList);
-
-    let _old_mesh = vec![[-20., -20., 0.], [1., 1., 0.], [0., 1., 0.], [1., 0., 0.]];
-
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, particles_mesh.0.clone());
-    // y: 50 to -50
-    // x : -250 to 250
+    let heatmap = images.add(image);
+    particles_mesh.heatmap = Some(heatmap.clone_weak());
+    commands
+        .spawn(SpriteBundle {
+            texture: heatmap,
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(SLIT_SCREEN_WIDTH - 2., SLIT_SCREEN_HEIGHT - 2.)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, 0.1)),
+            visibility: shown(ParticleLayer::Heatmap.shown_in(emitter.view)),
+            ..default()
+        })
+        .insert(ParticleLayer::Heatmap)
+        .insert(InputType::Particles);
 
-    // normals and UVs don't matter,
-    // so we just use the same value for all of them
-    mesh.insert_attribute(
-        Mesh::ATTRIBUTE_NORMAL,
-        vec![[0., 1., 0.]; particles_mesh.0.len()],
+    spawn_paths(
+        &mut commands,
+        &mut meshes,
+        &mut color_material,
+        &asset_server,
+        emitter.view,
     );
-    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; particles_mesh.0.len()]);
-
-    // color comes from wavelength
-    let color = wavelength_to_rgb(&slit.wavelength);
-    mesh.insert_attribute(
-        Mesh::ATTRIBUTE_COLOR,
-        vec![[color.r(), color.g(), color.b(), 1.]; particles_mesh.0.len()],
+    spawn_histogram(
+        &mut commands,
+        &mut meshes,
+        &mut color_material,
+        &asset_server,
     );
-
-    mesh
 }
 
-pub fn reset_particles(mut particles_mesh: ResMut<ParticlesMesh>) {
-    particles_mesh.reset_mesh();
+pub fn output_particles_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Particles)
 }
 
-pub fn reset_particles_criteria(slit_structure: Res<SlitStructure>) -> bool {
-    if slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Particles) {
-        true
-    } else {
-        false
+// copies just the particles that changed into the mesh on screen. bevy still sends
+// the whole vertex buffer over to the gpu after that, but ParticlesMesh keeps it bounded
+pub fn upload_particles(
+    mut particles_mesh: ResMut<ParticlesMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Some(mesh) = particles_mesh
+        .handle
+        .clone()
+        .and_then(|handle| meshes.get_mut(&handle))
+    else {
+        return;
+    };
+
+    // this isn't a change anyone else needs to hear about
+    let particles = particles_mesh.bypass_change_detection();
+    let Some(slots) = particles.take_changes() else {
+        *mesh = get_particles_mesh(particles);
+        return;
+    };
+
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    {
+        write_slots(positions, &slots, &particles.positions);
+    }
+    if let Some(VertexAttributeValues::Float32x4(colors)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+    {
+        write_slots(colors, &slots, &particles.colors);
+    }
+    let count = particles.positions.len();
+    if let Some(VertexAttributeValues::Float32x3(normals)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+    {
+        normals.resize(count, [0., 1., 0.]);
+    }
+    if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
+        uvs.resize(count, [0., 0.]);
     }
 }
 
-pub fn add_particle(
-    time: Res<Time>,
-    mut timer: ResMut<ParticleTimer>,
-    mut particles_mesh: ResMut<ParticlesMesh>,
-    slit: Res<SlitStructure>,
+// the dots only need keeping up while they're what's shown
+pub fn upload_particles_criteria(
+    slit_structure: Res<SlitStructure>,
+    particles_mesh: Res<ParticlesMesh>,
+    emitter: Res<ParticleEmitter>,
+) -> bool {
+    (particles_mesh.is_changed() || emitter.is_changed())
+        && matches!(emitter.view, ParticleView::Points)
+        && matches!(slit_structure.toggle_input, InputType::Particles)
+}
+
+// shades the heatmap from the grid, all of it since the grid is small
+pub fn upload_density(
+    particles_mesh: Res<ParticlesMesh>,
+    emitter: Res<ParticleEmitter>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    timer.0.tick(time.delta());
+    let ParticleView::Density(scale) = emitter.view else {
+        return;
+    };
+    if let Some(image) = particles_mesh
+        .heatmap
+        .as_ref()
+        .and_then(|handle| images.get_mut(handle))
+    {
+        draw_density(&particles_mesh.density, scale, &mut image.data);
+    }
+}
 
-    if timer.0.finished() {
-        for _ in 0..10 {
-            let new_coord = get_particle_coord(&slit);
+pub fn upload_density_criteria(
+    slit_structure: Res<SlitStructure>,
+    particles_mesh: Res<ParticlesMesh>,
+    emitter: Res<ParticleEmitter>,
+) -> bool {
+    (particles_mesh.is_changed() || emitter.is_changed())
+        && matches!(emitter.view, ParticleView::Density(_))
+        && matches!(slit_structure.toggle_input, InputType::Particles)
+}
 
-            particles_mesh.add_particle(new_coord);
-        }
+// swaps between the dots, the heatmap and the paths without touching any of them
+pub fn show_particle_view(
+    emitter: Res<ParticleEmitter>,
+    mut layer_query: Query<(&mut Visibility, &ParticleLayer)>,
+) {
+    for (mut visibility, layer) in layer_query.iter_mut() {
+        *visibility = shown(layer.shown_in(emitter.view));
     }
 }
 
-pub fn add_particles_criteria(slit_structure: Res<SlitStructure>) -> bool {
-    if matches!(slit_structure.toggle_input, InputType::Particles) {
-        true
+pub fn shown(visible: bool) -> Visibility {
+    if visible {
+        Visibility::Inherited
     } else {
-        false
+        Visibility::Hidden
     }
 }
 
-fn get_particle_coord(slit: &SlitStructure) -> [f32; 3] {
-    let mut rng = rand::thread_rng();
-
-    let x: f32;
-    let y: f32;
-
-    loop {
-        let x_prob: f32 = rng.gen(); // generates a float between 0 and 1
-        let b_prob: f32 = rng.gen();
-
-        let p_x = prob_x(x_prob, &slit);
-
-        if b_prob < p_x {
-            x = 498. * x_prob - 249.;
-            break;
+// empty cells let the screen through, busier ones go through HEAT_COLOR up to white
+fn draw_density(grid: &DensityGrid, scale: DensityScale, data: &mut [u8]) {
+    let heat = HEAT_COLOR.as_rgba_f32();
+    let white = [1.; 4];
+    let peak = grid.peak();
+
+    for j in 0..grid.rows {
+        for i in 0..grid.columns {
+            let count = grid.count(i, j);
+            let color = if count == 0 {
+                [0.; 4]
+            } else {
+                let u = scale.apply(count, peak);
+                let (from, to, t) = if u < 0.5 {
+                    (SCREEN_COLOR.as_rgba_f32(), heat, u * 2.)
+                } else {
+                    (heat, white, u * 2. - 1.)
+                };
+                let mut color = from;
+                for (c, t_c) in color.iter_mut().zip(to) {
+                    *c += (t_c - *c) * t;
+                }
+                color
+            };
+
+            // images start at the top
+            let k = ((grid.rows - 1 - j) * grid.columns + i) * 4;
+            for (byte, c) in data[k..k + 4].iter_mut().zip(color) {
+                *byte = (c.clamp(0., 1.) * 255.) as u8;
+            }
         }
     }
+}
 
-    loop {
-        let y_prob: f32 = rng.gen();
-        let b_prob: f32 = rng.gen();
-
-        let p_y = prob_y(y_prob, &slit);
-
-        if b_prob < p_y {
-            y = 98. * y_prob - 49.;
-            break;
+// the mesh's copy of `source`, with `slots` brought up to date.
+// slots past its end are new ones, and come in order
+fn write_slots<T: Copy>(values: &mut Vec<T>, slots: &[usize], source: &[T]) {
+    for &slot in slots {
+        if slot < values.len() {
+            values[slot] = source[slot];
+        } else {
+            values.push(source[slot]);
         }
     }
-
-    return [x, y, 0.];
 }
 
-fn prob_y(y: f32, slit: &SlitStructure) -> f32 {
-    let full_screen_width: f32 = 0.2; // m
-
-    let displacement: f32 = (y - 0.5) * full_screen_width;
+pub fn get_particles_mesh(particles_mesh: &ParticlesMesh) -> Mesh {
+    let mut mesh = Mesh::new(PointList);
 
-    let slit_height: f32 = 5. * 10e-6; // meters
-    let wavelength: f32 = slit.wavelength * 10e-9; // meters
-    let screen_distance: f32 = slit.screen_distance * 0.01; // meters
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, particles_mesh.positions.clone());
+    // y: 50 to -50
+    // x : -250 to 250
 
-    let sine_theta: f32 =
-        displacement / (displacement * displacement + screen_distance * screen_distance).sqrt();
+    // normals and UVs don't matter,
+    // so we just use the same value for all of them
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0., 1., 0.]; particles_mesh.positions.len()],
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vec![[0., 0.]; particles_mesh.positions.len()],
+    );
 
-    let coeff_a: f32 = ((3.1415 * slit_height) / wavelength) * sine_theta;
+    // color comes from each particle's wavelength
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, particles_mesh.colors.clone());
 
-    (coeff_a.sin() / coeff_a) * (coeff_a.sin() / coeff_a)
+    mesh
 }
 
-fn prob_x(x: f32, slit: &SlitStructure) -> f32 {
-    // x is in range 0 to 1
-    let full_screen_width: f32 = 0.4; // m
+// tabulating the pattern is the slow part, so it happens here once per change
+// rather than for every particle. the guiding waves go with it, line for line,
+// and so does the theory the histogram checks the particles against
+pub fn update_sampler(
+    slit_structure: Res<SlitStructure>,
+    far_field: Res<ApertureFarField>,
+    mut sampler: ResMut<ParticleSampler>,
+    mut guiding_waves: ResMut<GuidingWaves>,
+    mut theory: ResMut<ParticleTheory>,
+) {
+    let new = PatternSampler::new(&slit_structure, far_field.0.as_ref());
+    // masks have no slits for the paths to come out of
+    guiding_waves.0 = match far_field.0 {
+        Some(_) => vec![],
+        None => new
+            .lines
+            .iter()
+            .map(|line| GuidingWave::new(&slit_structure, line.wavelength))
+            .collect(),
+    };
+    sampler.0 = Some(new);
+    theory.0 = expected_shares(&slit_structure, far_field.0.as_ref(), THEORY_BINS);
+}
 
-    let displacement: f32 = (x - 0.5) * full_screen_width;
+pub fn update_sampler_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Particles)
+}
 
-    let separation: f32 = slit.separation * 10e-6; // meters
-    let slit_width: f32 = slit.slit_width * 10e-6; // meters
-    let wavelength: f32 = slit.wavelength * 10e-9; // meters
-    let screen_distance: f32 = slit.screen_distance * 0.01; // meters
+// a new run, starting over from the seed so the same setup gives the same particles
+pub fn reset_particles(
+    mut particles_mesh: ResMut<ParticlesMesh>,
+    mut rng: ResMut<ParticleRng>,
+    slit_structure: Res<SlitStructure>,
+) {
+    particles_mesh.reset_mesh();
+    rng.0 = StdRng::seed_from_u64(slit_structure.seed);
+}
 
-    let sine_theta: f32 =
-        displacement / (displacement * displacement + screen_distance * screen_distance).sqrt();
+pub fn reset_particles_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Particles)
+}
 
-    let coeff_a: f32 = ((3.1415 * slit_width) / wavelength) * sine_theta;
-    let probability: f32 = (coeff_a.sin() / coeff_a) * (coeff_a.sin() / coeff_a);
+pub fn add_particle(
+    fixed_time: Res<FixedTime>,
+    mut emitter: ResMut<ParticleEmitter>,
+    mut particles_mesh: ResMut<ParticlesMesh>,
+    mut rng: ResMut<ParticleRng>,
+    slit: Res<SlitStructure>,
+    sampler: Res<ParticleSampler>,
+    detector: Res<ParticleDetector>,
+) {
+    let Some(sampler) = sampler.0.as_ref() else {
+        return;
+    };
+    let detector = detector.model();
+
+    // this runs once per fixed step, so that's how long it's been.
+    // only the buttons count as changing it, the display doesn't care about the rest
+    let seconds = fixed_time.period.as_secs_f32();
+    let due = emitter.bypass_change_detection().due(seconds);
+    // same for the clock, only new hits count as the particles changing
+    particles_mesh.bypass_change_detection().tick(seconds);
+    if particles_mesh.capacity() != emitter.limit() {
+        particles_mesh.set_capacity(emitter.limit());
+    }
+    for _ in 0..due {
+        // nothing gets through, every slit is covered or dimmed all the way
+        // or the detector missed it
+        let Some(hit) = sampler
+            .draw(&mut rng.0)
+            .and_then(|hit| detector.detect(hit, &mut rng.0))
+        else {
+            continue;
+        };
+        let new_coord = [498. * hit.x - 249., 98. * hit.y - 49., 0.];
+
+        let color = match hit.tag {
+            Some(index) if slit.tag_slits => TAG_COLORS[index as usize % TAG_COLORS.len()],
+            _ => wavelength_to_rgb(&display_wavelength(
+                &slit,
+                sampler.lines[hit.line].wavelength,
+            )),
+        };
+        particles_mesh.add_particle(new_coord, color, hit.tag);
+        particles_mesh.density.add(hit.x, hit.y);
+        particles_mesh.remember(hit);
+    }
 
-    let coeff_b: f32 = ((3.1415 * separation) / wavelength) * sine_theta;
-    let interference: f32 = coeff_b.cos() * coeff_b.cos();
+    // the camera keeps counting while the emitter runs, particles or not.
+    // there's no path behind these, so they aren't remembered
+    if !emitter.paused {
+        for _ in 0..detector.dark_counts(seconds, &mut rng.0) {
+            let (x, y) = detector.dark_count(&mut rng.0);
+            particles_mesh.add_particle(
+                [498. * x - 249., 98. * y - 49., 0.],
+                DARK_COUNT_COLOR,
+                None,
+            );
+            particles_mesh.density.add(x, y);
+        }
+    }
+}
 
-    probability * interference // between 0 and 1
+pub fn add_particles_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    matches!(slit_structure.toggle_input, InputType::Particles)
 }
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::PrimitiveTopology::LineList;
+use bevy::sprite::{Anchor, MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::component::{
+    ArrivalPaths, EnsemblePaths, GuidingWaves, InputType, ParticleEmitter, ParticleLayer,
+    ParticleSampler, ParticleView, ParticlesMesh, SlitStructure,
+};
+use crate::physics::bohm::GuidingWave;
+use crate::WINDOW_HEIGHT;
+
+use super::particles::shown;
+use super::{flat_mesh, BASELINE_X_SLITS, SLIT_SCREEN_HEIGHT, SLIT_SCREEN_WIDTH};
+
+// how many paths make up the faint spread behind
+const ENSEMBLE_PATHS: usize = 40;
+
+const ENSEMBLE_COLOR: Color = Color::rgba(1., 1., 1., 0.25);
+const ARRIVAL_COLOR: Color = Color::rgb(0.9, 0.6, 0.3);
+
+// the paths side on, in the particle screen's place: the slits along the bottom and
+// the screen along the top. spawned empty with the rest of the particles,
+// update_ensemble and update_arrivals fill it in
+pub fn spawn_paths(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    asset_server: &AssetServer,
+    view: ParticleView,
+) {
+    let y = (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.;
+    let at = |z: f32| Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, z));
+    let visibility = shown(ParticleLayer::Paths.shown_in(view));
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(flat_mesh(LineList, vec![])).into(),
+            material: materials.add(ColorMaterial::from(ENSEMBLE_COLOR)),
+            transform: at(0.15),
+            visibility,
+            ..default()
+        })
+        .insert(EnsemblePaths)
+        .insert(ParticleLayer::Paths)
+        .insert(InputType::Particles);
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(flat_mesh(LineList, vec![])).into(),
+            material: materials.add(ColorMaterial::from(ARRIVAL_COLOR)),
+            transform: at(0.2),
+            visibility,
+            ..default()
+        })
+        .insert(ArrivalPaths)
+        .insert(ParticleLayer::Paths)
+        .insert(InputType::Particles);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "slits at the bottom, screen at the top, distance on a log scale",
+                TextStyle {
+                    font: asset_server
+                        .load("fonts/BigBlue TerminalPlus Nerd Font Complete Mono.TTF"),
+                    font_size: 8.0,
+                    color: ENSEMBLE_COLOR,
+                },
+            ),
+            text_anchor: Anchor::BottomLeft,
+            transform: Transform::from_translation(Vec3::new(
+                BASELINE_X_SLITS - SLIT_SCREEN_WIDTH / 2. + 4.,
+                y - SLIT_SCREEN_HEIGHT / 2. + 4.,
+                0.2,
+            )),
+            visibility,
+            ..default()
+        })
+        .insert(ParticleLayer::Paths)
+        .insert(InputType::Particles);
+}
+
+// where the whole ensemble goes, for the brightest line
+pub fn update_ensemble(
+    mut meshes: ResMut<Assets<Mesh>>,
+    sampler: Res<ParticleSampler>,
+    guiding_waves: Res<GuidingWaves>,
+    ensemble_query: Query<&Mesh2dHandle, With<EnsemblePaths>>,
+) {
+    let brightest = sampler.0.as_ref().and_then(|sampler| {
+        sampler
+            .lines
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.weight.total_cmp(&b.weight))
+            .map(|(i, _)| i)
+    });
+    let segments = match brightest.and_then(|i| guiding_waves.0.get(i)) {
+        Some(Some(wave)) => wave
+            .paths(ENSEMBLE_PATHS)
+            .iter()
+            .flat_map(|path| segments(wave, path))
+            .collect(),
+        _ => vec![],
+    };
+
+    for handle in ensemble_query.iter() {
+        if let Some(mesh) = meshes.get_mut(&handle.0) {
+            *mesh = flat_mesh(LineList, segments.clone());
+        }
+    }
+}
+
+pub fn update_ensemble_criteria(
+    slit_structure: Res<SlitStructure>,
+    guiding_waves: Res<GuidingWaves>,
+    new_paths: Query<(), Added<EnsemblePaths>>,
+) -> bool {
+    matches!(slit_structure.toggle_input, InputType::Particles)
+        && (guiding_waves.is_changed() || !new_paths.is_empty())
+}
+
+// the path each of the latest particles took to get where the born rule put it
+pub fn update_arrivals(
+    mut meshes: ResMut<Assets<Mesh>>,
+    particles_mesh: Res<ParticlesMesh>,
+    guiding_waves: Res<GuidingWaves>,
+    arrivals_query: Query<&Mesh2dHandle, With<ArrivalPaths>>,
+) {
+    let segments: Vec<[f32; 3]> = particles_mesh
+        .recent
+        .iter()
+        .filter_map(|hit| {
+            let wave = guiding_waves.0.get(hit.line)?.as_ref()?;
+            Some(segments(wave, &wave.path_to(hit.x)))
+        })
+        .flatten()
+        .collect();
+
+    for handle in arrivals_query.iter() {
+        if let Some(mesh) = meshes.get_mut(&handle.0) {
+            *mesh = flat_mesh(LineList, segments.clone());
+        }
+    }
+}
+
+// only while they're showing, there's no point tracing them back otherwise
+pub fn update_arrivals_criteria(
+    slit_structure: Res<SlitStructure>,
+    particles_mesh: Res<ParticlesMesh>,
+    emitter: Res<ParticleEmitter>,
+    new_paths: Query<(), Added<ArrivalPaths>>,
+) -> bool {
+    matches!(slit_structure.toggle_input, InputType::Particles)
+        && matches!(emitter.view, ParticleView::Paths)
+        && (particles_mesh.is_changed() || emitter.is_changed() || !new_paths.is_empty())
+}
+
+// `path` as line segments in the view, leaving out whatever goes off the sides.
+// height goes as GuidingWave::depth, and the view narrows from the screen's width at the
+// top down to just past the slits at the bottom, evenly on the same log scale. that way
+// the slits can be told apart and the top still lines up with the particles
+fn segments(wave: &GuidingWave, path: &[[f32; 2]]) -> Vec<[f32; 3]> {
+    let (half_width, half_height) = (
+        (SLIT_SCREEN_WIDTH - 2.) / 2.,
+        (SLIT_SCREEN_HEIGHT - 2.) / 2.,
+    );
+    let points: Vec<Option<[f32; 3]>> = path
+        .iter()
+        .map(|[x, z]| {
+            let depth = wave.depth(*z);
+            let width = (2. * wave.reach).powf(1. - depth) * wave.screen_width.powf(depth);
+            let across = x / width * 2. * half_width;
+            (across.abs() <= half_width).then_some([across, (2. * depth - 1.) * half_height, 0.])
+        })
+        .collect();
+
+    points
+        .windows(2)
+        .filter_map(|pair| Some([pair[0]?, pair[1]?]))
+        .flatten()
+        .collect()
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::PrimitiveTopology::LineStrip;
+use bevy::sprite::{Anchor, MaterialMesh2dBundle};
+
+use crate::component::{ApertureFarField, InputType, SlitStructure};
+use crate::physics::{envelope_x, profile, spectrum::spectrum, PROFILE_SAMPLES};
+use crate::ui::CONTROL_BACKGROUND;
+
+use super::{flat_mesh, BASELINE_X_SLITS, PLOT_HEIGHT, PLOT_Y, SCREEN_COLOR, SLIT_SCREEN_WIDTH};
+
+const PROFILE_COLOR: Color = Color::WHITE;
+const ENVELOPE_COLOR: Color = Color::rgb(0.9, 0.6, 0.3);
+
+// intensity across the middle of the screen under the light view, the same numbers
+// the shader gets, with the envelope the fringes sit under drawn on its own.
+// output_light despawns it along with the screen
+pub fn output_profile(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    slit_structure: Res<SlitStructure>,
+    far_field: Res<ApertureFarField>,
+    asset_server: Res<AssetServer>,
+) {
+    let profile = profile(&slit_structure, far_field.0.as_ref());
+    // masks don't have slits to take an envelope of. added up over the spectrum
+    // the same way as the profile
+    let envelope = far_field.0.is_none().then(|| {
+        let lines = spectrum(&slit_structure);
+        (0..PROFILE_SAMPLES)
+            .map(|i| {
+                let x = i as f32 / (PROFILE_SAMPLES - 1) as f32;
+                lines
+                    .iter()
+                    .map(|line| line.weight * envelope_x(&slit_structure, x, line.wavelength))
+                    .sum()
+            })
+            .collect::<Vec<f32>>()
+    });
+
+    // near field ringing can poke over 1, keep it all in the panel
+    let peak = profile
+        .iter()
+        .chain(envelope.iter().flatten())
+        .fold(1_f32, |peak, value| peak.max(*value));
+    let curve = |values: &[f32]| -> Vec<[f32; 3]> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                [
+                    (i as f32 / (values.len() - 1) as f32 - 0.5) * SLIT_SCREEN_WIDTH,
+                    (value.max(0.) / peak - 0.5) * PLOT_HEIGHT,
+                    0.,
+                ]
+            })
+            .collect()
+    };
+    let at = |z: f32| Transform::from_translation(Vec3::new(BASELINE_X_SLITS, PLOT_Y, z));
+
+    // backdrop
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::Box::new(SLIT_SCREEN_WIDTH, PLOT_HEIGHT, 0.).into())
+                .into(),
+            material: materials.add(ColorMaterial::from(SCREEN_COLOR)),
+            transform: at(0.),
+            ..default()
+        })
+        .insert(InputType::Light);
+
+    if let Some(envelope) = &envelope {
+        commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: meshes.add(flat_mesh(LineStrip, curve(envelope))).into(),
+                material: materials.add(ColorMaterial::from(ENVELOPE_COLOR)),
+                transform: at(0.1),
+                ..default()
+            })
+            .insert(InputType::Light);
+    }
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(flat_mesh(LineStrip, curve(&profile))).into(),
+            material: materials.add(ColorMaterial::from(PROFILE_COLOR)),
+            transform: at(0.2),
+            ..default()
+        })
+        .insert(InputType::Light);
+
+    let label = if envelope.is_some() {
+        "intensity across the screen, envelope in orange"
+    } else {
+        "intensity across the screen"
+    };
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                label,
+                TextStyle {
+                    font: asset_server
+                        .load("fonts/BigBlue TerminalPlus Nerd Font Complete Mono.TTF"),
+                    font_size: 8.0,
+                    color: CONTROL_BACKGROUND,
+                },
+            ),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform::from_translation(Vec3::new(
+                BASELINE_X_SLITS - SLIT_SCREEN_WIDTH / 2.,
+                PLOT_Y - PLOT_HEIGHT / 2. - 2.,
+                0.,
+            )),
+            ..default()
+        })
+        .insert(InputType::Light);
+}
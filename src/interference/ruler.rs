@@ -0,0 +1,175 @@
+use bevy::sprite::{Anchor, MaterialMesh2dBundle};
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::component::{InputType, Ruler, RulerCursor, RulerLabel, SlitStructure};
+use crate::physics::{screen_angle, screen_width};
+use crate::WINDOW_HEIGHT;
+
+use super::{BASELINE_X_SLITS, SLIT_SCREEN_HEIGHT, SLIT_SCREEN_WIDTH};
+
+const CURSOR_WIDTH: f32 = 1.;
+const CURSOR_COLOR: Color = Color::rgba(1., 1., 1., 0.8);
+// how close a click has to be to a cursor to pick it up, pixels
+const CURSOR_GRAB_RADIUS: f32 = 6.;
+
+// the cursors and their readout sit on top of whatever the screen is showing,
+// so they outlive the screen getting respawned
+pub fn setup_ruler(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    let y = screen_y();
+    for index in 0..2 {
+        commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: meshes
+                    .add(shape::Box::new(CURSOR_WIDTH, SLIT_SCREEN_HEIGHT, 0.).into())
+                    .into(),
+                material: materials.add(ColorMaterial::from(CURSOR_COLOR)),
+                transform: Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, 0.5)),
+                ..default()
+            })
+            .insert(RulerCursor(index));
+    }
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server
+                        .load("fonts/BigBlue TerminalPlus Nerd Font Complete Mono.TTF"),
+                    font_size: 8.0,
+                    color: CURSOR_COLOR,
+                },
+            ),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform::from_translation(Vec3::new(
+                BASELINE_X_SLITS - SLIT_SCREEN_WIDTH / 2. + 4.,
+                y + SLIT_SCREEN_HEIGHT / 2. - 4.,
+                0.5,
+            )),
+            ..default()
+        })
+        .insert(RulerLabel);
+}
+
+// press near a cursor to pick it up, or anywhere else on the screen to bring
+// the closest one over, and drag it around until letting go
+pub fn drag_ruler(
+    mouse: Res<Input<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    slit_structure: Res<SlitStructure>,
+    mut ruler: ResMut<Ruler>,
+) {
+    if mouse.just_released(MouseButton::Left) {
+        ruler.dragging = None;
+        return;
+    }
+    if !mouse.pressed(MouseButton::Left) || matches!(slit_structure.toggle_input, InputType::Wave) {
+        return;
+    }
+
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(cursor) = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    let left = BASELINE_X_SLITS - SLIT_SCREEN_WIDTH / 2.;
+    let x = ((cursor.x - left) / SLIT_SCREEN_WIDTH).clamp(0., 1.);
+
+    if mouse.just_pressed(MouseButton::Left) {
+        let on_screen = (cursor.x - BASELINE_X_SLITS).abs() <= SLIT_SCREEN_WIDTH / 2.
+            && (cursor.y - screen_y()).abs() <= SLIT_SCREEN_HEIGHT / 2.;
+        if !on_screen {
+            return;
+        }
+        let (nearest, distance) = ruler
+            .cursors
+            .iter()
+            .map(|at| (at - x).abs() * SLIT_SCREEN_WIDTH)
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        // a click right on a cursor shouldn't make it jump
+        if distance > CURSOR_GRAB_RADIUS {
+            ruler.cursors[nearest] = x;
+        }
+        ruler.dragging = Some(nearest);
+    } else if let Some(index) = ruler.dragging {
+        ruler.cursors[index] = x;
+    }
+}
+
+pub fn update_ruler(
+    ruler: Res<Ruler>,
+    slit_structure: Res<SlitStructure>,
+    mut cursor_query: Query<(&mut Transform, &mut Visibility, &RulerCursor), Without<RulerLabel>>,
+    mut label_query: Query<(&mut Text, &mut Visibility), With<RulerLabel>>,
+) {
+    // the wave view doesn't have a screen to measure
+    let visibility = match slit_structure.toggle_input {
+        InputType::Wave => Visibility::Hidden,
+        _ => Visibility::Inherited,
+    };
+
+    for (mut transform, mut cursor_visibility, cursor) in cursor_query.iter_mut() {
+        transform.translation.x =
+            BASELINE_X_SLITS + (ruler.cursors[cursor.0] - 0.5) * SLIT_SCREEN_WIDTH;
+        *cursor_visibility = visibility;
+    }
+
+    let [a, b] = ruler.cursors;
+    let across = screen_width(&slit_structure) * (a - b).abs();
+    let angle = (screen_angle(&slit_structure, a) - screen_angle(&slit_structure, b)).abs();
+    for (mut text, mut label_visibility) in label_query.iter_mut() {
+        text.sections[0].value = format!(
+            "cursors {} apart, {} ({:.3} deg)",
+            screen_length(across.meters()),
+            small_angle(angle),
+            angle.to_degrees()
+        );
+        *label_visibility = visibility;
+    }
+}
+
+pub fn update_ruler_criteria(ruler: Res<Ruler>, slit_structure: Res<SlitStructure>) -> bool {
+    ruler.is_changed() || slit_structure.is_changed()
+}
+
+fn screen_y() -> f32 {
+    (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.
+}
+
+// matter wave screens are tiny, so drop to micrometers when millimeters get silly
+fn screen_length(meters: f32) -> String {
+    if meters >= 1e-3 {
+        format!("{:.2} mm", meters * 1e3)
+    } else {
+        format!("{:.1} um", meters * 1e6)
+    }
+}
+
+// same for angles
+fn small_angle(radians: f32) -> String {
+    if radians >= 1e-3 {
+        format!("{:.2} mrad", radians * 1e3)
+    } else {
+        format!("{:.1} urad", radians * 1e6)
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    component::{InputType, ParticlesMesh, SlitStructure, WaveSimulation, MIN_WAVELENGTH},
+    physics::{
+        matter::display_wavelength,
+        spectrum::spectrum,
+        wave::{WaveField, SPONGE},
+    },
+    slit::{slit_scale, slit_translation, spectrum_to_rgb},
+    units::Wavelength,
+    WINDOW_HEIGHT,
+};
+
+use super::{BASELINE_X_SLITS, BORDER_COLOR, SCREEN_COLOR, SLIT_SCREEN_HEIGHT, SLIT_SCREEN_WIDTH};
+
+// one cell per pixel of the screen
+const WAVE_COLUMNS: usize = SLIT_SCREEN_WIDTH as usize;
+const WAVE_ROWS: usize = SLIT_SCREEN_HEIGHT as usize;
+// the slits as drawn are only a pixel or two wide, blow them up so the wave can see them
+const WAVE_ZOOM: f32 = 4.;
+// keep big gratings out of the side sponges
+const MAX_GRATING_CELLS: f32 = WAVE_COLUMNS as f32 - 2. * (SPONGE as f32 + 8.);
+const STEPS_PER_TICK: usize = 2;
+
+pub fn output_wave(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut simulation: ResMut<WaveSimulation>,
+    mut particles_mesh: ResMut<ParticlesMesh>,
+    slit_structure: Res<SlitStructure>,
+    screen_query: Query<Entity, With<InputType>>,
+) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    particles_mesh.reset_mesh();
+
+    // starts out still, the source fills it in
+    let field = wave_field(&slit_structure);
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: field.columns as u32,
+            height: field.rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    draw_field(&field, light_color(&slit_structure), &mut image.data);
+    simulation.0 = Some(field);
+
+    let y = (WINDOW_HEIGHT - SLIT_SCREEN_HEIGHT) / 2.;
+    commands
+        .spawn(SpriteBundle {
+            texture: images.add(image),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(SLIT_SCREEN_WIDTH, SLIT_SCREEN_HEIGHT)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(BASELINE_X_SLITS, y, 0.)),
+            ..default()
+        })
+        .insert(InputType::Wave);
+}
+
+pub fn wave_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    slit_structure.is_changed() && matches!(slit_structure.toggle_input, InputType::Wave)
+}
+
+pub fn step_wave(
+    mut simulation: ResMut<WaveSimulation>,
+    mut images: ResMut<Assets<Image>>,
+    slit_structure: Res<SlitStructure>,
+    screen_query: Query<&Handle<Image>, With<InputType>>,
+) {
+    if let Some(field) = simulation.0.as_mut() {
+        for _ in 0..STEPS_PER_TICK {
+            field.step();
+        }
+
+        let color = light_color(&slit_structure);
+        for handle in screen_query.iter() {
+            if let Some(image) = images.get_mut(handle) {
+                draw_field(field, color, &mut image.data);
+            }
+        }
+    }
+}
+
+pub fn step_wave_criteria(slit_structure: Res<SlitStructure>) -> bool {
+    matches!(slit_structure.toggle_input, InputType::Wave)
+}
+
+// the slits the way they're drawn, just bigger
+fn wave_field(slit: &SlitStructure) -> WaveField {
+    let last = slit.slit_count - 1;
+    let span = slit_translation(last, slit).x - slit_translation(0, slit).x
+        + (slit_scale(0, slit).x + slit_scale(last, slit).x) / 2.;
+    let zoom = WAVE_ZOOM.min(MAX_GRATING_CELLS / span);
+
+    // a wall can't let part of the wave through, so dimmed slits are all or nothing
+    let openings: Vec<(f32, f32)> = slit
+        .open_slits()
+        .filter(|&i| slit.transmissions[i as usize] > 0.)
+        .map(|i| {
+            let width = slit_scale(i, slit).x;
+            (slit_translation(i, slit).x * zoom, (width * zoom).max(1.))
+        })
+        .collect();
+    let lines: Vec<(f32, f32)> = spectrum(slit)
+        .iter()
+        .map(|line| {
+            let wavelength = display_wavelength(slit, line.wavelength);
+            (wavelength_cells(wavelength), line.weight)
+        })
+        .collect();
+
+    WaveField::new(WAVE_COLUMNS, WAVE_ROWS, &openings, &lines)
+}
+
+// 200nm to 800nm goes to 4 to 16 cells, any shorter and the grid can't keep up.
+// matter waves come in as their display wavelength, 380 to 780nm, so 7.6 to 15.6
+fn wavelength_cells(wavelength: Wavelength) -> f32 {
+    4. + (wavelength - MIN_WAVELENGTH) / Wavelength::nanometers(50.)
+}
+
+fn light_color(slit: &SlitStructure) -> Color {
+    let color = spectrum_to_rgb(slit, &spectrum(slit));
+    if color.r() + color.g() + color.b() == 0. {
+        // we cannot actually see this light, but we still want to watch it
+        Color::GRAY
+    } else {
+        color
+    }
+}
+
+// crests in the light's color, troughs dark, walls like the border
+fn draw_field(field: &WaveField, light: Color, data: &mut [u8]) {
+    let background = SCREEN_COLOR.as_rgba_f32();
+    let light = light.as_rgba_f32();
+    let wall = BORDER_COLOR.as_rgba_f32();
+    let dark = [0., 0., 0., 1.];
+
+    for j in 0..field.rows {
+        for i in 0..field.columns {
+            let color = if field.is_wall(i, j) {
+                wall
+            } else {
+                let u = field.value(i, j).clamp(-1., 1.);
+                let target = if u > 0. { light } else { dark };
+                let mut color = background;
+                for (c, t) in color.iter_mut().zip(target) {
+                    *c += (t - *c) * u.abs();
+                }
+                color
+            };
+
+            // images start at the top
+            let k = ((field.rows - 1 - j) * field.columns + i) * 4;
+            for (byte, c) in data[k..k + 4].iter_mut().zip(color) {
+                *byte = (c.clamp(0., 1.) * 255.) as u8;
+            }
+        }
+    }
+}
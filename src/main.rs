@@ -9,8 +9,10 @@ use ui::BACKDROUND_COLOR;
 
 mod component;
 mod interference;
+mod physics;
 mod slit;
 mod ui;
+mod units;
 
 // keep these at a 1:2 ratio or the physics math doesn't work out
 // if you change my ratio. change it in the shaders too!
@@ -38,6 +40,16 @@ fn main() {
         .run();
 }
 
+// the particles can be seeded at launch, with `--seed 42` or DOUBLE_SLIT_SEED=42,
+// so a run can be repeated exactly. 0 if neither is given
+pub fn launch_seed() -> u64 {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    args.next()
+        .or_else(|| std::env::var("DOUBLE_SLIT_SEED").ok())
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(0)
+}
+
 fn setup_camera(mut commands: Commands) {
     // ui camera
     commands.spawn(Camera2dBundle::default());
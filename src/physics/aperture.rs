@@ -0,0 +1,399 @@
+// apertures the slit math can't describe (uneven slits, grids, holes, whatever you draw).
+// anything given as a transmission mask gets its far field pattern from an fft instead
+
+use crate::component::{Aperture, SlitStructure};
+use crate::units::{Length, Wavelength};
+
+use super::complex::Complex;
+use super::fft::fft_2d;
+use super::matter::slit_unit;
+use super::{screen_height, screen_width, sinc, sine_theta};
+
+// masks are plain meters inside, they never leave this file
+// pixel size for masks that only vary across x
+const STRIP_PIXEL: Length = Length::nanometers(250.);
+// 2d masks get coarser pixels if they wouldn't fit in this many across
+const MAX_MASK_PIXELS_2D: usize = 256;
+const MIN_PIXEL_2D: Length = Length::nanometers(1000.);
+// zero padding, more means finer steps between angles on the screen
+const PADDING: usize = 16;
+const MAX_FFT_1D: usize = 1 << 16;
+const MAX_FFT_2D: usize = 1 << 10;
+// each pixel is averaged over this many points per side so edges don't alias so badly
+const SUPERSAMPLE: usize = 4;
+
+// draw your own! '#' lets light through, anything else blocks it.
+// each character is one slit width across, top row first
+pub const CUSTOM_MASK: [&str; 7] = [
+    "#########",
+    "....#....",
+    "....#....",
+    "....#....",
+    "....#....",
+    "....#....",
+    "....#....",
+];
+
+/// a sampled transmission mask, centered on the optical axis
+pub struct ApertureMask {
+    pub columns: usize,
+    // 1 for masks that only vary across x, like slits
+    pub rows: usize,
+    pub pixel_width: f32,  // meters
+    pub pixel_height: f32, // meters, unused when rows is 1
+    // row major starting from the bottom, 0 is opaque and 1 is clear
+    pub transmission: Vec<f32>,
+}
+
+impl ApertureMask {
+    /// a strip `width` meters across, tall like a slit, sampled from `transmission(x)`
+    pub fn strip(width: f32, transmission: impl Fn(f32) -> f32) -> Self {
+        let pixel = STRIP_PIXEL.meters();
+        let columns = ((width / pixel).ceil() as usize).max(1);
+        let left = -(columns as f32) * pixel / 2.;
+
+        let transmission = (0..columns)
+            .map(|i| {
+                (0..SUPERSAMPLE)
+                    .map(|s| {
+                        let offset = (s as f32 + 0.5) / SUPERSAMPLE as f32;
+                        transmission(left + (i as f32 + offset) * pixel)
+                    })
+                    .sum::<f32>()
+                    / SUPERSAMPLE as f32
+            })
+            .collect();
+
+        ApertureMask {
+            columns,
+            rows: 1,
+            pixel_width: pixel,
+            pixel_height: 0.,
+            transmission,
+        }
+    }
+
+    /// a `width` x `height` meter patch sampled from `transmission(x, y)`
+    pub fn patch(width: f32, height: f32, transmission: impl Fn(f32, f32) -> f32) -> Self {
+        let pixel = MIN_PIXEL_2D
+            .meters()
+            .max(width.max(height) / MAX_MASK_PIXELS_2D as f32);
+        let columns = ((width / pixel).ceil() as usize).max(1);
+        let rows = ((height / pixel).ceil() as usize).max(2);
+        let left = -(columns as f32) * pixel / 2.;
+        let bottom = -(rows as f32) * pixel / 2.;
+
+        let mut values = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let mut total = 0.;
+                for sy in 0..SUPERSAMPLE {
+                    for sx in 0..SUPERSAMPLE {
+                        let x =
+                            left + (column as f32 + (sx as f32 + 0.5) / SUPERSAMPLE as f32) * pixel;
+                        let y =
+                            bottom + (row as f32 + (sy as f32 + 0.5) / SUPERSAMPLE as f32) * pixel;
+                        total += transmission(x, y);
+                    }
+                }
+                values.push(total / (SUPERSAMPLE * SUPERSAMPLE) as f32);
+            }
+        }
+
+        ApertureMask {
+            columns,
+            rows,
+            pixel_width: pixel,
+            pixel_height: pixel,
+            transmission: values,
+        }
+    }
+
+    /// the same mask `factor` times the size
+    pub fn scaled(self, factor: f32) -> Self {
+        ApertureMask {
+            pixel_width: self.pixel_width * factor,
+            pixel_height: self.pixel_height * factor,
+            ..self
+        }
+    }
+
+    /// ascii art mask, '#' is clear and anything else is opaque. top row first
+    pub fn from_ascii(art: &[&str], pixel: f32) -> Self {
+        let columns = art.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let rows = art.len();
+
+        let mut transmission = vec![0.; columns * rows];
+        for (y, row) in art.iter().rev().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    transmission[y * columns + x] = 1.;
+                }
+            }
+        }
+
+        ApertureMask {
+            columns,
+            rows,
+            pixel_width: pixel,
+            pixel_height: pixel,
+            transmission,
+        }
+    }
+}
+
+/// the mask for the current aperture, or None for plain slits (those get the exact math)
+pub fn mask_for(slit: &SlitStructure) -> Option<ApertureMask> {
+    // drawn as if the units were micrometers, then shrunk for matter waves.
+    // keeps the pixel sizes sensible either way
+    let scale = slit_unit(slit) / Length::micrometers(1.);
+    micrometer_mask(slit).map(|mask| mask.scaled(scale))
+}
+
+fn micrometer_mask(slit: &SlitStructure) -> Option<ApertureMask> {
+    let count = slit.slit_count as f32;
+    let in_micrometers = |length: Length| Length::micrometers(length / slit_unit(slit)).meters();
+    let separation = in_micrometers(slit.separation);
+    let slit_width = in_micrometers(slit.slit_width);
+
+    match slit.aperture {
+        Aperture::Slits => None,
+        Aperture::Uneven => {
+            // slits get wider from left to right, up to twice the slit width,
+            // on top of whatever each one's been set to
+            let width_of = |i: u32| {
+                slit_width * (1. + i as f32 / (count - 1.).max(1.)) * slit.width_scales[i as usize]
+            };
+            let widest = (0..slit.slit_count).map(width_of).fold(0., f32::max);
+            let total = (count - 1.) * separation + widest;
+
+            Some(ApertureMask::strip(total, |x| {
+                slit.open_slits()
+                    .find(|&i| {
+                        let center = (i as f32 - (count - 1.) / 2.) * separation;
+                        (x - center).abs() < width_of(i) / 2.
+                    })
+                    .map_or(0., |i| slit.transmissions[i as usize])
+            }))
+        }
+        Aperture::Grid => {
+            // slit count x slit count square holes, slit width on a side, separation apart
+            let total = (count - 1.) * separation + slit_width;
+            let hole = |v: f32| {
+                let from_first = v + (count - 1.) * separation / 2.;
+                let nearest = (from_first / separation).round().clamp(0., count - 1.);
+                (from_first - nearest * separation).abs() < slit_width / 2.
+            };
+
+            Some(ApertureMask::patch(total, total, |x, y| {
+                if hole(x) && hole(y) {
+                    1.
+                } else {
+                    0.
+                }
+            }))
+        }
+        Aperture::Circle => {
+            // one round hole, separation across
+            let radius = separation / 2.;
+            Some(ApertureMask::patch(separation, separation, |x, y| {
+                if x * x + y * y < radius * radius {
+                    1.
+                } else {
+                    0.
+                }
+            }))
+        }
+        Aperture::Custom => Some(ApertureMask::from_ascii(&CUSTOM_MASK, slit_width)),
+    }
+}
+
+/// |fourier transform|^2 of a mask, i.e. its fraunhofer pattern
+pub struct FarField {
+    columns: usize,
+    rows: usize,
+    pixel_width: f32,
+    pixel_height: f32,
+    // normalized so straight ahead is 1
+    power: Vec<f32>,
+}
+
+impl FarField {
+    pub fn new(mask: &ApertureMask) -> Self {
+        let columns = padded(
+            mask.columns,
+            if mask.rows == 1 {
+                MAX_FFT_1D
+            } else {
+                MAX_FFT_2D
+            },
+        );
+        let rows = if mask.rows == 1 {
+            1
+        } else {
+            padded(mask.rows, MAX_FFT_2D)
+        };
+
+        // anything past the fft size gets cropped off, the padding keeps that from happening
+        let mut data = vec![Complex::ZERO; columns * rows];
+        for y in 0..mask.rows.min(rows) {
+            for x in 0..mask.columns.min(columns) {
+                data[y * columns + x] = Complex::new(mask.transmission[y * mask.columns + x], 0.);
+            }
+        }
+
+        fft_2d(&mut data, columns, rows);
+
+        // the zero frequency is (total transmission)^2, nothing else can beat it
+        let peak = data[0].norm_sqr();
+        let power = data
+            .iter()
+            .map(|val| if peak > 0. { val.norm_sqr() / peak } else { 0. })
+            .collect();
+
+        FarField {
+            columns,
+            rows,
+            pixel_width: mask.pixel_width,
+            pixel_height: mask.pixel_height,
+            power,
+        }
+    }
+
+    /// whether the pattern varies in y too
+    pub fn is_2d(&self) -> bool {
+        self.rows > 1
+    }
+
+    /// intensity at spatial frequency (`fx`, `fy`) in cycles per meter, between 0 and 1
+    pub fn power_at(&self, fx: f32, fy: f32) -> f32 {
+        // the fft repeats, the square pixels are what make it fall off
+        let u = (fx * self.columns as f32 * self.pixel_width).rem_euclid(self.columns as f32);
+        let pixel_x = sinc(std::f32::consts::PI * self.pixel_width * fx);
+
+        if !self.is_2d() {
+            return lerp_wrapped(&self.power, u) * pixel_x * pixel_x;
+        }
+
+        let v = (fy * self.rows as f32 * self.pixel_height).rem_euclid(self.rows as f32);
+        let pixel_y = sinc(std::f32::consts::PI * self.pixel_height * fy);
+
+        let row = v.floor() as usize % self.rows;
+        let next_row = (row + 1) % self.rows;
+        let below = lerp_wrapped(&self.power[row * self.columns..(row + 1) * self.columns], u);
+        let above = lerp_wrapped(
+            &self.power[next_row * self.columns..(next_row + 1) * self.columns],
+            u,
+        );
+
+        (below + (above - below) * v.fract()) * pixel_x * pixel_x * pixel_y * pixel_y
+    }
+
+    /// intensity at screen position (`x`, `y`), both 0 to 1, for one `wavelength`,
+    /// between 0 and 1. `y` only matters for 2d masks
+    pub fn intensity_at(
+        &self,
+        slit: &SlitStructure,
+        x: f32,
+        y: f32,
+        wavelength: Wavelength,
+    ) -> f32 {
+        let wavelength = wavelength.meters();
+        let across = (x - 0.5) * screen_width(slit).meters();
+
+        if !self.is_2d() {
+            return self.power_at(sine_theta(across, slit.screen_distance) / wavelength, 0.);
+        }
+
+        let screen_distance = slit.screen_distance.meters();
+        let up = (y - 0.5) * screen_height(slit).meters();
+        let distance = (across * across + up * up + screen_distance * screen_distance).sqrt();
+
+        self.power_at(across / distance / wavelength, up / distance / wavelength)
+    }
+}
+
+fn padded(size: usize, max: usize) -> usize {
+    (size * PADDING).next_power_of_two().min(max)
+}
+
+// linear interpolation at fractional index `u`, wrapping around the end
+fn lerp_wrapped(values: &[f32], u: f32) -> f32 {
+    let i = u.floor() as usize % values.len();
+    let next = (i + 1) % values.len();
+    values[i] + (values[next] - values[i]) * u.fract()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{intensity_x, single_slit, FULL_SCREEN_HEIGHT, FULL_SCREEN_WIDTH};
+    use crate::units::Distance;
+
+    #[test]
+    fn single_strip_matches_slit_envelope() {
+        let slit = SlitStructure::default();
+        let slit_width = slit.slit_width.meters();
+        let mask = ApertureMask::strip(
+            slit_width,
+            |x| {
+                if x.abs() < slit_width / 2. {
+                    1.
+                } else {
+                    0.
+                }
+            },
+        );
+        let far_field = FarField::new(&mask);
+
+        for x in [0.5, 0.52, 0.6, 0.75] {
+            let sine_theta =
+                sine_theta((x - 0.5) * FULL_SCREEN_WIDTH.meters(), slit.screen_distance);
+            let expected = single_slit(sine_theta, slit.slit_width, slit.wavelength);
+            assert!(
+                (far_field.intensity_at(&slit, x, 0.5, slit.wavelength) - expected).abs() < 0.01
+            );
+        }
+    }
+
+    #[test]
+    fn even_slits_match_double_slit() {
+        let slit = SlitStructure::default();
+        let separation = slit.separation.meters();
+        let slit_width = slit.slit_width.meters();
+        let mask = ApertureMask::strip(separation + slit_width, |x| {
+            if (x.abs() - separation / 2.).abs() < slit_width / 2. {
+                1.
+            } else {
+                0.
+            }
+        });
+        let far_field = FarField::new(&mask);
+
+        for x in [0.5, 0.5025, 0.505, 0.51, 0.53] {
+            let expected = intensity_x(&slit, x, slit.wavelength);
+            assert!(
+                (far_field.intensity_at(&slit, x, 0.5, slit.wavelength) - expected).abs() < 0.02
+            );
+        }
+    }
+
+    #[test]
+    fn circle_is_round() {
+        let slit = SlitStructure {
+            aperture: Aperture::Circle,
+            ..SlitStructure::default()
+        };
+        let far_field = FarField::new(&mask_for(&slit).unwrap());
+        assert!(far_field.is_2d());
+
+        // same distance out along x and y should look the same
+        let out = Distance::centimeters(0.5);
+        let across =
+            far_field.intensity_at(&slit, 0.5 + out / FULL_SCREEN_WIDTH, 0.5, slit.wavelength);
+        let up =
+            far_field.intensity_at(&slit, 0.5, 0.5 + out / FULL_SCREEN_HEIGHT, slit.wavelength);
+        assert!((across - up).abs() < 0.02);
+        assert!((far_field.intensity_at(&slit, 0.5, 0.5, slit.wavelength) - 1.).abs() < 1e-4);
+    }
+}
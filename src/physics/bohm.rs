@@ -0,0 +1,308 @@
+// de broglie-bohm. the particle has a definite position the whole way, and the wave
+// only steers it: past the slits (small angles, z the distance from the slits) it moves
+// along the wave's phase gradient, dx/dz = Im(psi' / psi) / k.
+// paths never cross, so whatever share of the particles starts out left of a path is
+// still left of it at the screen. that's how the born rule comes back out at the end.
+//
+// to have the wave in closed form everywhere past the slits, each slit is stood in for by
+// a gaussian with the same spread. the fringes land where the real slits put them, only
+// the envelope around them comes out gaussian instead of sinc^2.
+// it's a pure state through the open slits, so the which path detector, a wide source
+// and the polarizers don't come into it.
+// f64 inside for the same reason as fresnel.rs, the phases run into the thousands
+
+use std::f64::consts::PI;
+
+use crate::component::SlitStructure;
+use crate::units::Wavelength;
+
+use super::complex::Complex;
+use super::sampling::Cdf;
+use super::{plate_phase, screen_width, slit_center, width_of, PROFILE_SAMPLES};
+
+// how many steps a path takes from the slits to the screen
+const PATH_STEPS: usize = 800;
+// how many points across the slits the starting positions get tabulated at
+const START_SAMPLES: usize = 1024;
+// how many spreads out from the outer slits a particle can still start
+const START_REACH: f64 = 4.;
+// steeper than this is somewhere the wave is dark, where a path never actually goes
+const MAX_SLOPE: f32 = 1.;
+
+// the gaussian standing in for one slit
+#[derive(Debug, Clone)]
+struct Beam {
+    center: f64,        // meters from the middle of the grating
+    spread: f64,        // standard deviation across the slit, meters
+    log_amplitude: f64, // ln of the amplitude right behind the slit
+    phase: f64,         // what the plate adds
+}
+
+// the beams added up
+#[derive(Debug, Clone)]
+struct Superposition {
+    k: f64, // per meter
+    beams: Vec<Beam>,
+}
+
+/// the wave behind the slits for one wavelength, and where particles guided by it start and end up
+#[derive(Debug, Clone)]
+pub struct GuidingWave {
+    wave: Superposition,
+    screen_distance: f64,
+    /// how wide the screen is, meters
+    pub screen_width: f32,
+    /// how far either side of the middle particles can start out, meters
+    pub reach: f32,
+    // the shortest distance any beam takes to start spreading out, meters.
+    // paths are stepped evenly in ln(1 + z / near_field), see `depth`
+    near_field: f64,
+    starts: Cdf,   // over -reach to reach at the slits
+    arrivals: Cdf, // over the screen, 0 to 1
+}
+
+impl GuidingWave {
+    /// None if nothing gets through the slits
+    pub fn new(slit: &SlitStructure, wavelength: Wavelength) -> Option<Self> {
+        let delay = plate_phase(slit, wavelength) as f64;
+        let beams: Vec<Beam> = slit
+            .open_slits()
+            .filter(|&i| slit.transmissions[i as usize] > 0.)
+            .map(|i| {
+                // a wider slit lets more in, same as slit_power
+                let amplitude =
+                    slit.transmissions[i as usize] * slit.width_scales[i as usize].sqrt();
+                Beam {
+                    center: slit_center(slit, i).meters() as f64,
+                    // a flat slit's standard deviation
+                    spread: width_of(slit, i).meters() as f64 / 12_f64.sqrt(),
+                    log_amplitude: (amplitude as f64).ln(),
+                    phase: if i == slit.plate_slit { delay } else { 0. },
+                }
+            })
+            .collect();
+        if beams.is_empty() {
+            return None;
+        }
+
+        let k = 2. * PI / wavelength.meters() as f64;
+        let reach = beams
+            .iter()
+            .map(|beam| beam.center.abs() + START_REACH * beam.spread)
+            .fold(0., f64::max);
+        // the gaussian's own rayleigh range, 2 k sigma^2
+        let near_field = beams
+            .iter()
+            .map(|beam| 2. * k * beam.spread * beam.spread)
+            .fold(f64::INFINITY, f64::min);
+
+        let wave = Superposition { k, beams };
+        let screen_distance = slit.screen_distance.meters() as f64;
+        let screen_width = screen_width(slit).meters() as f64;
+
+        let starts: Vec<f32> = (0..START_SAMPLES)
+            .map(|i| {
+                let x = reach * (2. * i as f64 / (START_SAMPLES - 1) as f64 - 1.);
+                wave.density(x, 0.) as f32
+            })
+            .collect();
+        let arrivals: Vec<f32> = (0..PROFILE_SAMPLES)
+            .map(|i| {
+                let x = i as f64 / (PROFILE_SAMPLES - 1) as f64 - 0.5;
+                wave.density(x * screen_width, screen_distance) as f32
+            })
+            .collect();
+
+        Some(GuidingWave {
+            wave,
+            screen_distance,
+            screen_width: screen_width as f32,
+            reach: reach as f32,
+            near_field,
+            starts: Cdf::from_samples(&starts)?,
+            arrivals: Cdf::from_samples(&arrivals)?,
+        })
+    }
+
+    /// the path of the particle that starts out `u` (0 to 1) of the way through the
+    /// spread of starting positions, from the slits to the screen.
+    /// (x, z) in meters, x from the middle of the grating and z out from the slits
+    pub fn path_from(&self, u: f32) -> Vec<[f32; 2]> {
+        let mut x = self.reach as f64 * (2. * self.starts.sample(u) as f64 - 1.);
+
+        // z = near_field (e^s - 1), so the near field gets as many steps as the far field
+        let end = (1. + self.screen_distance / self.near_field).ln();
+        let ds = end / PATH_STEPS as f64;
+        let z = |s: f64| self.near_field * s.exp_m1();
+        let dx_ds = |x: f64, s: f64| {
+            let z = z(s);
+            self.wave.slope(x, z) as f64 * (z + self.near_field)
+        };
+
+        let mut path = Vec::with_capacity(PATH_STEPS + 1);
+        path.push([x as f32, 0.]);
+        for step in 0..PATH_STEPS {
+            let s = step as f64 * ds;
+            let k1 = dx_ds(x, s);
+            let k2 = dx_ds(x + k1 * ds / 2., s + ds / 2.);
+            let k3 = dx_ds(x + k2 * ds / 2., s + ds / 2.);
+            let k4 = dx_ds(x + k3 * ds, s + ds);
+            x += (k1 + 2. * k2 + 2. * k3 + k4) * ds / 6.;
+            path.push([x as f32, z(s + ds) as f32]);
+        }
+        path
+    }
+
+    /// the path that ends up at `x` (0 to 1) across the screen.
+    /// it's the one that starts as far through the starting positions as `x` is through
+    /// the arrivals, since the paths keep their order
+    pub fn path_to(&self, x: f32) -> Vec<[f32; 2]> {
+        self.path_from(self.arrivals.below(x))
+    }
+
+    /// `count` paths spread evenly through the starting positions, the ensemble as a whole
+    pub fn paths(&self, count: usize) -> Vec<Vec<[f32; 2]>> {
+        (0..count)
+            .map(|i| self.path_from((i as f32 + 0.5) / count as f32))
+            .collect()
+    }
+
+    /// how far from the slits (0) to the screen (1) `z` meters is, on the log scale the paths
+    /// are stepped on. the beams spread out over a tiny fraction of the way, so this is
+    /// what to draw them against
+    pub fn depth(&self, z: f32) -> f32 {
+        ((z as f64 / self.near_field).ln_1p() / (self.screen_distance / self.near_field).ln_1p())
+            as f32
+    }
+}
+
+impl Superposition {
+    // every beam's psi at (x, z), all scaled by the same factor to keep them in range,
+    // and that factor's ln. along with how each one changes across, over k
+    fn beams_at(&self, x: f64, z: f64) -> (Vec<(Complex, Complex)>, f64) {
+        let terms: Vec<((f64, f64), (f64, f64))> = self
+            .beams
+            .iter()
+            .map(|beam| {
+                // psi = a (4 sigma^2 / q)^(1/2) exp(-(x - c)^2 / q), q = 4 sigma^2 + 2 i z / k
+                let q = (4. * beam.spread * beam.spread, 2. * z / self.k);
+                let norm = q.0 * q.0 + q.1 * q.1;
+                let inverse = (q.0 / norm, -q.1 / norm);
+                let d = x - beam.center;
+
+                let log_psi = (
+                    beam.log_amplitude + 0.5 * (q.0 / norm.sqrt()).ln() - d * d * inverse.0,
+                    beam.phase - 0.5 * q.1.atan2(q.0) - d * d * inverse.1,
+                );
+                // d psi / dx = -2 (x - c) / q psi
+                let gradient = (-2. * d * inverse.0 / self.k, -2. * d * inverse.1 / self.k);
+                (log_psi, gradient)
+            })
+            .collect();
+
+        let scale = terms
+            .iter()
+            .map(|((log_magnitude, _), _)| *log_magnitude)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let beams = terms
+            .into_iter()
+            .map(|((log_magnitude, phase), gradient)| {
+                let psi = Complex::from_phase(phase.rem_euclid(2. * PI) as f32)
+                    * ((log_magnitude - scale).exp() as f32);
+                (psi, Complex::new(gradient.0 as f32, gradient.1 as f32))
+            })
+            .collect();
+        (beams, scale)
+    }
+
+    // |psi|^2 at (x, z)
+    fn density(&self, x: f64, z: f64) -> f64 {
+        let (beams, scale) = self.beams_at(x, z);
+        let psi = beams
+            .iter()
+            .fold(Complex::ZERO, |total, (psi, _)| total + *psi);
+        psi.norm_sqr() as f64 * (2. * scale).exp()
+    }
+
+    // dx/dz for a particle at (x, z)
+    fn slope(&self, x: f64, z: f64) -> f32 {
+        let (beams, _) = self.beams_at(x, z);
+        let (psi, gradient) = beams.iter().fold(
+            (Complex::ZERO, Complex::ZERO),
+            |(psi, gradient), (beam, beam_gradient)| {
+                (psi + *beam, gradient + *beam * *beam_gradient)
+            },
+        );
+        if psi.norm_sqr() == 0. {
+            return 0.;
+        }
+        ((gradient * psi.conj()).im / psi.norm_sqr()).clamp(-MAX_SLOPE, MAX_SLOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::Source;
+    use crate::physics::spectrum::spectrum;
+    use crate::units::Length;
+
+    fn double_slit() -> GuidingWave {
+        let slit = SlitStructure::default();
+        GuidingWave::new(&slit, slit.wavelength).unwrap()
+    }
+
+    #[test]
+    fn paths_keep_their_place_in_line() {
+        // a path that starts a share u of the way through the particles
+        // ends up that share of the way through the arrivals.
+        // electrons too, where everything is a thousand times smaller
+        let electrons = SlitStructure {
+            source: Source::Electron,
+            separation: Length::nanometers(50.),
+            slit_width: Length::nanometers(5.),
+            ..SlitStructure::default()
+        };
+        let electron_wave = spectrum(&electrons)[0].wavelength;
+        for wave in [
+            double_slit(),
+            GuidingWave::new(&electrons, electron_wave).unwrap(),
+        ] {
+            for i in 1..20 {
+                let u = i as f32 / 20.;
+                let end = wave.path_from(u).last().unwrap()[0];
+                let x = end / wave.screen_width + 0.5;
+                assert!((wave.arrivals.below(x) - u).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn paths_never_cross_the_middle() {
+        // two even slits, so the middle is a line of symmetry no path can get over
+        let wave = double_slit();
+        for path in wave.paths(40) {
+            let side = path[0][0].signum();
+            assert!(path.iter().all(|[x, _]| x.signum() == side));
+        }
+    }
+
+    #[test]
+    fn paths_end_where_they_were_sent() {
+        let wave = double_slit();
+        // the middle fringe and the next ones out, 1 cm apart on a 40 cm screen
+        for x in [0.5, 0.525, 0.475] {
+            let end = wave.path_to(x).last().unwrap()[0];
+            assert!((end / wave.screen_width + 0.5 - x).abs() < 0.003);
+        }
+    }
+
+    #[test]
+    fn covered_slits_steer_nothing() {
+        let slit = SlitStructure {
+            covered: [true; crate::component::MAX_SLIT_COUNT as usize],
+            ..SlitStructure::default()
+        };
+        assert!(GuidingWave::new(&slit, slit.wavelength).is_none());
+    }
+}
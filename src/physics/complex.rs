@@ -0,0 +1,75 @@
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+// just enough complex numbers to add up the light from each slit
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex { re: 0., im: 0. };
+
+    pub fn new(re: f32, im: f32) -> Self {
+        Complex { re, im }
+    }
+
+    /// magnitude 1 at angle `phase`
+    pub fn from_phase(phase: f32) -> Self {
+        Complex {
+            re: phase.cos(),
+            im: phase.sin(),
+        }
+    }
+
+    pub fn conj(&self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// |z|^2, i.e. the intensity if this is an amplitude
+    pub fn norm_sqr(&self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl AddAssign for Complex {
+    fn add_assign(&mut self, rhs: Complex) {
+        self.re += rhs.re;
+        self.im += rhs.im;
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Mul<f32> for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: f32) -> Complex {
+        Complex::new(self.re * rhs, self.im * rhs)
+    }
+}
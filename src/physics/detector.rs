@@ -0,0 +1,269 @@
+// the camera at the screen. the sampler says where a particle lands, this says what the
+// camera makes of it: it misses some, blurs the rest a little and files each one under
+// a pixel. and now and then it counts something that was never there
+
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use super::sampling::Hit;
+use super::{FULL_SCREEN_HEIGHT, FULL_SCREEN_WIDTH};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detector {
+    /// the share of the particles that get counted, 0 to 1
+    pub efficiency: f32,
+    /// counts a second from nothing, spread evenly over the screen
+    pub dark_rate: f32,
+    /// standard deviation of where a count ends up around where the particle landed,
+    /// as a share of the screen's width
+    pub blur: f32,
+    /// how many pixels across, None to keep exact positions. square, so the screen
+    /// gets as many rows as fit
+    pub pixels: Option<usize>,
+}
+
+impl Default for Detector {
+    // a perfect one, every particle exactly where it landed
+    fn default() -> Self {
+        Detector {
+            efficiency: 1.,
+            dark_rate: 0.,
+            blur: 0.,
+            pixels: None,
+        }
+    }
+}
+
+impl Detector {
+    /// what the camera records for `hit`, None if it missed it
+    /// or the blur took it off the edge
+    pub fn detect(&self, hit: Hit, rng: &mut impl Rng) -> Option<Hit> {
+        // a perfect detector leaves rng alone, so a seed gives the same particles as ever
+        if self.efficiency < 1. && rng.gen::<f32>() >= self.efficiency {
+            return None;
+        }
+        let (mut x, mut y) = (hit.x, hit.y);
+        if self.blur > 0. {
+            x += self.blur * normal(rng);
+            y += self.blur * aspect() * normal(rng);
+        }
+        let (x, y) = self.snap(x, y)?;
+        Some(Hit { x, y, ..hit })
+    }
+
+    /// how many dark counts come up in `seconds`, poisson distributed
+    pub fn dark_counts(&self, seconds: f32, rng: &mut impl Rng) -> u32 {
+        // knuth's, which runs out of f32 past a mean of about 80. a sum of poissons
+        // is poisson too, so anything bigger goes in pieces
+        let mean = self.dark_rate * seconds;
+        let pieces = (mean / 10.).ceil().max(1.) as u32;
+        let limit = (-mean / pieces as f32).exp();
+        let mut count = 0;
+        for _ in 0..pieces {
+            let mut product = rng.gen::<f32>();
+            while product > limit {
+                count += 1;
+                product *= rng.gen::<f32>();
+            }
+        }
+        count
+    }
+
+    /// where a dark count shows up, (x, y) each 0 to 1
+    pub fn dark_count(&self, rng: &mut impl Rng) -> (f32, f32) {
+        // anywhere in 0 to 1 is on the screen
+        self.snap(rng.gen(), rng.gen()).unwrap()
+    }
+
+    /// the share of the counts that are dark, with particles coming in at `per_second`
+    pub fn dark_share(&self, per_second: f32) -> f32 {
+        let total = self.dark_rate + self.efficiency * per_second;
+        if total > 0. {
+            self.dark_rate / total
+        } else {
+            0.
+        }
+    }
+
+    /// what the camera turns `shares` (the share of the particles landing in each of a
+    /// fine row of equal bins across the screen) into, counted up in `bins` equal bins:
+    /// blurred, filed under pixels, and with `dark_share` of it spread evenly
+    pub fn smear(&self, shares: &[f32], bins: usize, dark_share: f32) -> Vec<f32> {
+        let cells = shares.len();
+        let cell = |i: usize| (i as f32 + 0.5) / cells as f32;
+
+        // whatever's blurred off the edge is gone
+        let blurred: Vec<f32> = if self.blur > 0. {
+            let spread = self.blur * cells as f32;
+            let reach = (4. * spread).ceil() as isize;
+            (0..cells)
+                .map(|i| {
+                    (-reach..=reach)
+                        .filter_map(|offset| {
+                            let j = i.checked_add_signed(offset).filter(|&j| j < cells)?;
+                            let weight = (-0.5 * (offset as f32 / spread).powi(2)).exp();
+                            Some(shares[j] * weight)
+                        })
+                        .sum()
+                })
+                .collect()
+        } else {
+            shares.to_vec()
+        };
+
+        // each fine bin goes to the pixel under its middle, and lands where that pixel is
+        let mut counts = vec![0.; bins];
+        let mut file = |x: f32, share: f32| {
+            let x = self.snap(x, 0.5).map_or(x, |(x, _)| x);
+            counts[((x * bins as f32) as usize).min(bins - 1)] += share;
+        };
+        let total: f32 = blurred.iter().sum();
+        for (i, share) in blurred.iter().enumerate() {
+            if total > 0. {
+                file(cell(i), (1. - dark_share) * share / total);
+            }
+            file(cell(i), dark_share / cells as f32);
+        }
+
+        let total: f32 = counts.iter().sum();
+        if total > 0. {
+            for count in counts.iter_mut() {
+                *count /= total;
+            }
+        }
+        counts
+    }
+
+    // the middle of the pixel (`x`, `y`) is in, or just (`x`, `y`) without pixels.
+    // None if it's off the screen
+    fn snap(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        if !(0. ..=1.).contains(&x) || !(0. ..=1.).contains(&y) {
+            return None;
+        }
+        let Some(columns) = self.pixels else {
+            return Some((x, y));
+        };
+        let rows = (columns as f32 / aspect()).round().max(1.) as usize;
+        let middle = |value: f32, cells: usize| {
+            let cell = ((value * cells as f32) as usize).min(cells - 1);
+            (cell as f32 + 0.5) / cells as f32
+        };
+        Some((middle(x, columns), middle(y, rows)))
+    }
+}
+
+// how much wider the screen is than tall
+fn aspect() -> f32 {
+    FULL_SCREEN_WIDTH / FULL_SCREEN_HEIGHT
+}
+
+// one draw from a standard normal, box-muller
+fn normal(rng: &mut impl Rng) -> f32 {
+    let radius = (-2. * (1. - rng.gen::<f32>()).ln()).sqrt();
+    radius * (2. * PI * rng.gen::<f32>()).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::SlitStructure;
+    use crate::physics::histogram::{bin_counts, chi_square, expected_shares};
+    use crate::physics::sampling::PatternSampler;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn hit(x: f32, y: f32) -> Hit {
+        Hit {
+            line: 0,
+            tag: None,
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn perfect_detector_changes_nothing() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let detector = Detector::default();
+        assert_eq!(
+            detector.detect(hit(0.3, 0.7), &mut rng),
+            Some(hit(0.3, 0.7))
+        );
+        assert_eq!(detector.dark_counts(100., &mut rng), 0);
+    }
+
+    #[test]
+    fn efficiency_drops_its_share() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let detector = Detector {
+            efficiency: 0.3,
+            ..Detector::default()
+        };
+        let counted = (0..10000)
+            .filter(|_| detector.detect(hit(0.5, 0.5), &mut rng).is_some())
+            .count();
+        assert!((counted as f32 / 10000. - 0.3).abs() < 0.02);
+    }
+
+    #[test]
+    fn dark_counts_come_at_their_rate() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let detector = Detector {
+            dark_rate: 120.,
+            ..Detector::default()
+        };
+        // a minute of 60 steps a second
+        let total: u32 = (0..3600)
+            .map(|_| detector.dark_counts(1. / 60., &mut rng))
+            .sum();
+        assert!((total as f32 / 60. - 120.).abs() < 5.);
+    }
+
+    #[test]
+    fn pixels_file_hits_under_their_middles() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let detector = Detector {
+            pixels: Some(10),
+            ..Detector::default()
+        };
+        // 10 across makes 5 up on a screen twice as wide as tall
+        let recorded = detector.detect(hit(0.33, 0.01), &mut rng).unwrap();
+        assert!((recorded.x - 0.35).abs() < 1e-6);
+        assert!((recorded.y - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn camera_data_fits_the_smeared_pattern() {
+        let slit = SlitStructure::default();
+        let sampler = PatternSampler::new(&slit, None);
+        let detector = Detector {
+            efficiency: 0.5,
+            dark_rate: 500.,
+            blur: 0.004,
+            pixels: Some(200),
+        };
+        let mut rng = StdRng::seed_from_u64(5);
+
+        // a second at 2000 particles a second
+        let mut xs: Vec<f32> = (0..2000)
+            .filter_map(|_| detector.detect(sampler.draw(&mut rng)?, &mut rng))
+            .map(|hit| hit.x)
+            .collect();
+        for _ in 0..detector.dark_counts(1., &mut rng) {
+            xs.push(detector.dark_count(&mut rng).0);
+        }
+
+        let bins = 100;
+        let expected: Vec<f32> = detector
+            .smear(
+                &expected_shares(&slit, None, 1000),
+                bins,
+                detector.dark_share(2000.),
+            )
+            .iter()
+            .map(|share| share * xs.len() as f32)
+            .collect();
+        let (value, freedom) = chi_square(&bin_counts(xs, bins), &expected).unwrap();
+        assert!(value < 2. * freedom as f32);
+    }
+}
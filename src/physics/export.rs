@@ -0,0 +1,190 @@
+// getting the numbers out for a notebook. plain csv with the setup they came from in
+// comment lines up top, so pandas.read_csv(path, comment="#") reads it as is.
+// positions are in meters from the middle of the screen, the same way up as on screen
+
+use crate::component::SlitStructure;
+
+use super::aperture::FarField;
+use super::detector::Detector;
+use super::spectrum::spectrum;
+use super::{profile, screen_height, screen_width, PROFILE_SAMPLES};
+
+/// one hit on the screen as it goes out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrival {
+    /// seconds since the screen was cleared
+    pub time: f32,
+    /// 0 to 1 across and up the screen, like sampling::Hit
+    pub x: f32,
+    pub y: f32,
+    /// the slit the which path detector caught it at
+    pub tag: Option<u32>,
+}
+
+/// `arrivals` one a row, oldest first if they come in that order.
+/// the slit column is empty for the ones that went through unseen
+pub fn hits_csv(slit: &SlitStructure, detector: &Detector, arrivals: &[Arrival]) -> String {
+    let (width, height) = (screen_width(slit).meters(), screen_height(slit).meters());
+    let mut csv = setup(slit, detector);
+    csv.push_str("time_s,x_m,y_m,slit\n");
+    for arrival in arrivals {
+        let tag = arrival.tag.map_or(String::new(), |tag| tag.to_string());
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            arrival.time,
+            (arrival.x - 0.5) * width,
+            (arrival.y - 0.5) * height,
+            tag
+        ));
+    }
+    csv
+}
+
+/// the theory across the middle of the screen, what the light view plots under it.
+/// peaks at about 1, same as physics::profile
+pub fn profile_csv(
+    slit: &SlitStructure,
+    detector: &Detector,
+    far_field: Option<&FarField>,
+) -> String {
+    let width = screen_width(slit).meters();
+    let mut csv = setup(slit, detector);
+    csv.push_str("x_m,intensity\n");
+    for (i, value) in profile(slit, far_field).iter().enumerate() {
+        let x = i as f32 / (PROFILE_SAMPLES - 1) as f32 - 0.5;
+        csv.push_str(&format!("{},{}\n", x * width, value));
+    }
+    csv
+}
+
+// what made the numbers, one `# name,value` line each
+fn setup(slit: &SlitStructure, detector: &Detector) -> String {
+    let mut lines = vec![
+        ("source".to_string(), slit.source.to_string()),
+        ("aperture".to_string(), slit.aperture.to_string()),
+        ("slit_count".to_string(), slit.slit_count.to_string()),
+        (
+            "slit_width_m".to_string(),
+            slit.slit_width.meters().to_string(),
+        ),
+        (
+            "separation_m".to_string(),
+            slit.separation.meters().to_string(),
+        ),
+        (
+            "screen_distance_m".to_string(),
+            slit.screen_distance.meters().to_string(),
+        ),
+        (
+            "screen_width_m".to_string(),
+            screen_width(slit).meters().to_string(),
+        ),
+        (
+            "screen_height_m".to_string(),
+            screen_height(slit).meters().to_string(),
+        ),
+        (
+            "source_width_m".to_string(),
+            slit.source_width.meters().to_string(),
+        ),
+        ("coherence".to_string(), slit.coherence.to_string()),
+        ("diffraction".to_string(), slit.diffraction.to_string()),
+        ("which_path".to_string(), slit.which_path.to_string()),
+        ("seed".to_string(), slit.seed.to_string()),
+    ];
+    // wavelength and how much of the light is at it, a line each
+    for line in spectrum(slit) {
+        lines.push((
+            "line_m".to_string(),
+            format!("{},{}", line.wavelength.meters(), line.weight),
+        ));
+    }
+    lines.extend([
+        (
+            "detector_efficiency".to_string(),
+            detector.efficiency.to_string(),
+        ),
+        (
+            "detector_dark_rate".to_string(),
+            detector.dark_rate.to_string(),
+        ),
+        ("detector_blur".to_string(), detector.blur.to_string()),
+        (
+            "detector_pixels".to_string(),
+            detector
+                .pixels
+                .map_or("off".to_string(), |pixels| pixels.to_string()),
+        ),
+    ]);
+
+    lines
+        .iter()
+        .map(|(name, value)| format!("# {},{}\n", name, value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the rows under the header, split on commas
+    fn rows(csv: &str) -> Vec<Vec<String>> {
+        csv.lines()
+            .filter(|line| !line.starts_with('#'))
+            .skip(1)
+            .map(|line| line.split(',').map(str::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn hits_go_out_in_meters() {
+        let slit = SlitStructure::default();
+        let arrivals = [
+            Arrival {
+                time: 0.5,
+                x: 0.5,
+                y: 0.5,
+                tag: None,
+            },
+            Arrival {
+                time: 1.,
+                x: 1.,
+                y: 0.,
+                tag: Some(1),
+            },
+        ];
+        let rows = rows(&hits_csv(&slit, &Detector::default(), &arrivals));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], ["0.5", "0", "0", ""]);
+
+        // the far corner of a 40 by 20 cm screen
+        let x: f32 = rows[1][1].parse().unwrap();
+        let y: f32 = rows[1][2].parse().unwrap();
+        assert!((x - 0.2).abs() < 1e-6);
+        assert!((y + 0.1).abs() < 1e-6);
+        assert_eq!(rows[1][3], "1");
+    }
+
+    #[test]
+    fn profile_peaks_in_the_middle() {
+        let slit = SlitStructure::default();
+        let csv = profile_csv(&slit, &Detector::default(), None);
+        assert!(csv.contains("# slit_count,2\n"));
+
+        let rows = rows(&csv);
+        assert_eq!(rows.len(), PROFILE_SAMPLES);
+        let values: Vec<(f32, f32)> = rows
+            .iter()
+            .map(|row| (row[0].parse().unwrap(), row[1].parse().unwrap()))
+            .collect();
+        let (peak_x, peak) =
+            values.iter().fold(
+                (0., 0.),
+                |best, &(x, value)| if value > best.1 { (x, value) } else { best },
+            );
+        // the samples straddle the middle, half a sample either side of it,
+        // so the top of the middle fringe falls just between two of them
+        assert!(peak_x.abs() < screen_width(&slit).meters() / PROFILE_SAMPLES as f32);
+        assert!((peak - 1.).abs() < 0.05);
+    }
+}
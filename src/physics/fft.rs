@@ -0,0 +1,88 @@
+use std::f32::consts::PI;
+
+use super::complex::Complex;
+
+/// in place radix 2 fft. `data.len()` has to be a power of 2
+pub fn fft(data: &mut [Complex]) {
+    let n = data.len();
+    assert!(n.is_power_of_two(), "fft length {n} isn't a power of 2");
+
+    // shuffle into bit reversed order
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let twiddles: Vec<Complex> = (0..half)
+            .map(|k| Complex::from_phase(-2. * PI * k as f32 / len as f32))
+            .collect();
+
+        for start in (0..n).step_by(len) {
+            for (k, twiddle) in twiddles.iter().enumerate() {
+                let a = data[start + k];
+                let b = data[start + k + half] * *twiddle;
+                data[start + k] = a + b;
+                data[start + k + half] = a - b;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// in place 2d fft of a row major `columns` x `rows` grid, both powers of 2
+pub fn fft_2d(data: &mut [Complex], columns: usize, rows: usize) {
+    for row in data.chunks_exact_mut(columns) {
+        fft(row);
+    }
+
+    if rows == 1 {
+        return;
+    }
+
+    let mut column = vec![Complex::ZERO; rows];
+    for x in 0..columns {
+        for (y, val) in column.iter_mut().enumerate() {
+            *val = data[y * columns + x];
+        }
+        fft(&mut column);
+        for (y, val) in column.iter().enumerate() {
+            data[y * columns + x] = *val;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_naive_dft() {
+        let input: Vec<Complex> = (0..16)
+            .map(|i| Complex::new((i as f32 * 0.7).sin(), (i as f32 * 0.3).cos()))
+            .collect();
+
+        let mut output = input.clone();
+        fft(&mut output);
+
+        for (k, got) in output.iter().enumerate() {
+            let expected = input
+                .iter()
+                .enumerate()
+                .fold(Complex::ZERO, |total, (i, val)| {
+                    total + *val * Complex::from_phase(-2. * PI * (i * k) as f32 / 16.)
+                });
+            assert!((*got - expected).norm_sqr() < 1e-8);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+// near field diffraction. everything in here is f64 on purpose:
+// the phases run into the millions of radians at the edges of the screen
+// and f32 turns them into noise
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use super::complex::Complex;
+
+/// the fresnel integrals C(x) = ∫0^x cos(pi t^2 / 2) dt and S(x) = ∫0^x sin(pi t^2 / 2) dt.
+/// power series near 0 and a continued fraction further out, after numerical recipes
+pub fn fresnel_integrals(x: f64) -> (f64, f64) {
+    const EPSILON: f64 = 1e-12;
+    const TINY: f64 = 1e-300;
+    const MAX_ITERATIONS: usize = 200;
+    const SERIES_LIMIT: f64 = 1.5;
+
+    let ax = x.abs();
+
+    let (c, s) = if ax < TINY.sqrt() {
+        (ax, 0.)
+    } else if ax <= SERIES_LIMIT {
+        // C and S share one series, odd terms go to S and even terms to C
+        let fact = FRAC_PI_2 * ax * ax;
+        let mut term = ax;
+        let mut sum_c = ax;
+        let mut sum_s = 0.;
+        let mut sign = 1.;
+        let mut n = 3.;
+        for k in 1..MAX_ITERATIONS {
+            term *= fact / k as f64;
+            let contribution = sign * term / n;
+            if k % 2 == 1 {
+                sum_s += contribution;
+                sign = -sign;
+            } else {
+                sum_c += contribution;
+            }
+            if term < EPSILON * sum_c.abs().max(sum_s.abs()) {
+                break;
+            }
+            n += 2.;
+        }
+        (sum_c, sum_s)
+    } else {
+        // modified lentz for the continued fraction of erfc
+        let pix2 = PI * ax * ax;
+        let mut b = (1., -pix2);
+        let mut cc = (1. / TINY, 0.);
+        let mut d = complex_inverse(b);
+        let mut h = d;
+        let mut n = -1.;
+        for _ in 2..MAX_ITERATIONS {
+            n += 2.;
+            let a = -n * (n + 1.);
+            b = (b.0 + 4., b.1);
+            d = complex_inverse(complex_add(complex_scale(d, a), b));
+            cc = complex_add(b, complex_scale(complex_inverse(cc), a));
+            let del = complex_mul(cc, d);
+            h = complex_mul(h, del);
+            if (del.0 - 1.).abs() + del.1.abs() < EPSILON {
+                break;
+            }
+        }
+        h = complex_mul((ax, -ax), h);
+        let phase = (0.5 * pix2).cos();
+        let phase_im = (0.5 * pix2).sin();
+        let one_minus = complex_add(
+            (1., 0.),
+            complex_scale(complex_mul((phase, phase_im), h), -1.),
+        );
+        let cs = complex_mul((0.5, 0.5), one_minus);
+        (cs.0, cs.1)
+    };
+
+    if x < 0. {
+        (-c, -s)
+    } else {
+        (c, s)
+    }
+}
+
+/// near field amplitude at `screen_x` from a slit running from `left` to `right`,
+/// everything in meters, using the paraxial fresnel approximation.
+/// normalized so that far away, straight ahead, it has magnitude 1
+pub fn fresnel_slit(
+    screen_x: f64,
+    left: f64,
+    right: f64,
+    wavelength: f64,
+    screen_distance: f64,
+) -> Complex {
+    let scale = (2. / (wavelength * screen_distance)).sqrt();
+    let (c_left, s_left) = fresnel_integrals(scale * (left - screen_x));
+    let (c_right, s_right) = fresnel_integrals(scale * (right - screen_x));
+    let norm = 1. / (scale * (right - left));
+
+    Complex::new(
+        ((c_right - c_left) * norm) as f32,
+        ((s_right - s_left) * norm) as f32,
+    )
+}
+
+// f64 complex helpers, only needed for the continued fraction
+fn complex_add(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+fn complex_scale(a: (f64, f64), k: f64) -> (f64, f64) {
+    (a.0 * k, a.1 * k)
+}
+
+fn complex_mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn complex_inverse(a: (f64, f64)) -> (f64, f64) {
+    let norm = a.0 * a.0 + a.1 * a.1;
+    (a.0 / norm, -a.1 / norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        // from abramowitz & stegun table 7.7
+        for (x, c, s) in [
+            (0.5, 0.4923442, 0.0647324),
+            (1., 0.7798934, 0.4382591),
+            (1.5, 0.4452612, 0.6975050),
+            (2., 0.4882534, 0.3434157),
+            (3., 0.6057208, 0.4963130),
+        ] {
+            let (got_c, got_s) = fresnel_integrals(x);
+            assert!((got_c - c).abs() < 1e-6, "C({x}) = {got_c}, expected {c}");
+            assert!((got_s - s).abs() < 1e-6, "S({x}) = {got_s}, expected {s}");
+        }
+    }
+
+    #[test]
+    fn odd_and_heads_to_one_half() {
+        let (c, s) = fresnel_integrals(-1.);
+        assert!((c + 0.7798934).abs() < 1e-6 && (s + 0.4382591).abs() < 1e-6);
+
+        let (c, s) = fresnel_integrals(1e4);
+        assert!((c - 0.5).abs() < 1e-4 && (s - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn narrow_slit_far_away_is_unit_amplitude() {
+        let amplitude = fresnel_slit(0., -0.5e-6, 0.5e-6, 500e-9, 1.);
+        assert!((amplitude.norm_sqr() - 1.).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,310 @@
+// counting up where the particles landed, and checking those counts against
+// what the pattern says they should be
+
+use crate::component::SlitStructure;
+
+use super::aperture::FarField;
+use super::spectrum::spectrum;
+use super::{intensity, slit_power, tagged_intensity_x, untagged_intensity_x};
+
+// bins expecting fewer hits than this get lumped in with their neighbours
+// before the chi-square, the usual rule of thumb for it to mean anything
+pub const MIN_EXPECTED: f32 = 5.;
+
+// how many points across the screen expected_shares looks at the intensity, at least.
+// about a dozen to a fringe for the narrowest patterns
+const THEORY_SAMPLES: usize = 1000;
+// and how many heights it averages a 2d mask over
+const THEORY_ROWS: usize = 16;
+
+/// how many of `xs` (each 0 to 1) land in each of `bins` equal bins.
+/// anything outside goes in the nearest end bin
+pub fn bin_counts(xs: impl IntoIterator<Item = f32>, bins: usize) -> Vec<f32> {
+    let mut counts = vec![0.; bins];
+    for x in xs {
+        let bin = (x * bins as f32).max(0.) as usize;
+        counts[bin.min(bins - 1)] += 1.;
+    }
+    counts
+}
+
+/// pearson's chi-square of `observed` counts against `expected` ones, with its
+/// degrees of freedom. sparse bins are pooled with the ones after them until they
+/// expect at least `MIN_EXPECTED`, so None until there are enough hits for a verdict
+pub fn chi_square(observed: &[f32], expected: &[f32]) -> Option<(f32, usize)> {
+    let mut groups: Vec<(f32, f32)> = vec![];
+    let mut pending = (0., 0.);
+    for (o, e) in observed.iter().zip(expected) {
+        pending = (pending.0 + o, pending.1 + e);
+        if pending.1 >= MIN_EXPECTED {
+            groups.push(pending);
+            pending = (0., 0.);
+        }
+    }
+    // whatever's left over at the end goes with the last group
+    match groups.last_mut() {
+        Some(last) => *last = (last.0 + pending.0, last.1 + pending.1),
+        None => return None,
+    }
+
+    let freedom = groups.len() - 1;
+    if freedom == 0 {
+        return None;
+    }
+    let chi_square = groups.iter().map(|(o, e)| (o - e) * (o - e) / e).sum();
+    Some((chi_square, freedom))
+}
+
+/// the share of the particles that should land in each of `bins` equal bins across the
+/// screen. this comes straight from the intensity and not from the sampler's tables, so a
+/// sampler that gets the pattern wrong shows up against it. each line goes in by how
+/// bright it is, and the ones the which path detector catches land as their slit alone
+/// would put them, in proportion to how much light each slit lets through
+pub fn expected_shares(
+    slit: &SlitStructure,
+    far_field: Option<&FarField>,
+    bins: usize,
+) -> Vec<f32> {
+    let per_bin = (THEORY_SAMPLES as f32 / bins as f32).ceil() as usize;
+    // `intensity` added up over each bin, as shares of the whole screen
+    let shares = |intensity: &dyn Fn(f32) -> f32| -> Vec<f32> {
+        let masses: Vec<f32> = (0..bins)
+            .map(|bin| {
+                (0..per_bin)
+                    .map(|k| {
+                        let x = (bin as f32 + (k as f32 + 0.5) / per_bin as f32) / bins as f32;
+                        intensity(x).max(0.)
+                    })
+                    .sum()
+            })
+            .collect();
+        normalized(masses)
+    };
+
+    let paths: Vec<(u32, f32)> = slit
+        .open_slits()
+        .map(|i| (i, slit_power(slit, i)))
+        .filter(|(_, power)| *power > 0.)
+        .collect();
+    let total_power: f32 = paths.iter().map(|(_, power)| power).sum();
+    // masks don't have slits to watch
+    let which_path = if far_field.is_none() && !paths.is_empty() {
+        slit.which_path
+    } else {
+        0.
+    };
+
+    let mut expected = vec![0.; bins];
+    let mut add = |weight: f32, shares: Vec<f32>| {
+        for (expected, share) in expected.iter_mut().zip(shares) {
+            *expected += weight * share;
+        }
+    };
+    for line in spectrum(slit) {
+        let wavelength = line.wavelength;
+        match far_field {
+            Some(far_field) => {
+                let rows = if far_field.is_2d() { THEORY_ROWS } else { 1 };
+                add(
+                    line.weight,
+                    shares(&|x| {
+                        (0..rows)
+                            .map(|j| {
+                                let y = (j as f32 + 0.5) / rows as f32;
+                                intensity(slit, Some(far_field), x, y, wavelength)
+                            })
+                            .sum()
+                    }),
+                );
+            }
+            None => {
+                add(
+                    line.weight * (1. - which_path),
+                    shares(&|x| untagged_intensity_x(slit, x, wavelength)),
+                );
+                if which_path > 0. {
+                    for (index, power) in &paths {
+                        add(
+                            line.weight * which_path * power / total_power,
+                            shares(&|x| tagged_intensity_x(slit, x, wavelength, *index)),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // a line that's dark all over adds nothing, the rest still adds up to 1
+    normalized(expected)
+}
+
+// `values` scaled to add up to 1, or left alone if they're all 0
+fn normalized(mut values: Vec<f32>) -> Vec<f32> {
+    let total: f32 = values.iter().sum();
+    if total > 0. {
+        for value in values.iter_mut() {
+            *value /= total;
+        }
+    }
+    values
+}
+
+/// how a cell's count turns into how bright it's drawn, against the busiest cell's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DensityScale {
+    Linear,
+    // squashes the peaks, the middle comes up
+    Sqrt,
+    // single hits still show next to cells with millions
+    Log,
+}
+
+impl DensityScale {
+    /// 0 for an empty cell up to 1 for the busiest one
+    pub fn apply(self, count: u32, peak: u32) -> f32 {
+        if peak == 0 {
+            return 0.;
+        }
+        let (count, peak) = (count as f32, peak as f32);
+        match self {
+            DensityScale::Linear => count / peak,
+            DensityScale::Sqrt => (count / peak).sqrt(),
+            DensityScale::Log => count.ln_1p() / peak.ln_1p(),
+        }
+    }
+}
+
+/// every hit since the last reset, counted up in a grid over the screen.
+/// unlike the particles themselves this doesn't grow, so it can take millions
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    pub columns: usize,
+    pub rows: usize,
+    counts: Vec<u32>, // a row at a time, starting from y = 0
+    peak: u32,
+    total: u64,
+}
+
+impl DensityGrid {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        DensityGrid {
+            columns,
+            rows,
+            counts: vec![0; columns * rows],
+            peak: 0,
+            total: 0,
+        }
+    }
+
+    /// a hit at `x`, `y` (each 0 to 1), anything outside goes in the nearest edge cell
+    pub fn add(&mut self, x: f32, y: f32) {
+        let cell =
+            |value: f32, cells: usize| ((value * cells as f32).max(0.) as usize).min(cells - 1);
+        let index = cell(y, self.rows) * self.columns + cell(x, self.columns);
+        self.counts[index] = self.counts[index].saturating_add(1);
+        self.peak = self.peak.max(self.counts[index]);
+        self.total += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.peak = 0;
+        self.total = 0;
+    }
+
+    pub fn count(&self, column: usize, row: usize) -> u32 {
+        self.counts[row * self.columns + column]
+    }
+
+    pub fn peak(&self) -> u32 {
+        self.peak
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::SlitStructure;
+    use crate::physics::sampling::PatternSampler;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn edges_land_in_the_end_bins() {
+        assert_eq!(bin_counts([0., 0.3, 0.99, 1.], 4), vec![1., 1., 0., 2.]);
+    }
+
+    #[test]
+    fn sparse_bins_get_pooled() {
+        // 2 + 3 pool into one group, then 10 and 10
+        let expected = [2., 3., 10., 10.];
+        let (value, freedom) = chi_square(&expected, &expected).unwrap();
+        assert_eq!(freedom, 2);
+        assert!(value.abs() < 1e-6);
+
+        // not enough hits to say anything yet
+        assert!(chi_square(&[1., 1.], &[1., 1.]).is_none());
+    }
+
+    #[test]
+    fn particles_fit_the_theory() {
+        let slit = SlitStructure {
+            which_path: 0.3,
+            ..SlitStructure::default()
+        };
+        let sampler = PatternSampler::new(&slit, None);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let bins = 100;
+        let draws = 20000;
+        let counts = bin_counts(
+            (0..draws)
+                .filter_map(|_| sampler.draw(&mut rng))
+                .map(|hit| hit.x),
+            bins,
+        );
+        let expected: Vec<f32> = expected_shares(&slit, None, bins)
+            .iter()
+            .map(|fraction| fraction * draws as f32)
+            .collect();
+
+        // a fair fit has chi-square around its degrees of freedom
+        let (value, freedom) = chi_square(&counts, &expected).unwrap();
+        assert!(value < 2. * freedom as f32);
+    }
+
+    #[test]
+    fn density_grid_counts_where_hits_land() {
+        let mut grid = DensityGrid::new(4, 2);
+        grid.add(0.1, 0.1);
+        grid.add(0.1, 0.2);
+        grid.add(0.9, 0.9);
+        // off the edge, into the top right corner
+        grid.add(1.5, 2.);
+
+        assert_eq!(grid.count(0, 0), 2);
+        assert_eq!(grid.count(3, 1), 2);
+        assert_eq!(grid.count(1, 0), 0);
+        assert_eq!((grid.peak(), grid.total()), (2, 4));
+
+        grid.clear();
+        assert_eq!((grid.peak(), grid.total(), grid.count(0, 0)), (0, 0, 0));
+    }
+
+    #[test]
+    fn scales_agree_at_the_ends() {
+        for scale in [DensityScale::Linear, DensityScale::Sqrt, DensityScale::Log] {
+            assert_eq!(scale.apply(0, 1000), 0.);
+            assert!((scale.apply(1000, 1000) - 1.).abs() < 1e-6);
+            assert_eq!(scale.apply(0, 0), 0.);
+        }
+        // one hit next to a million is invisible on a linear scale but not a log one
+        let faint = |scale: DensityScale| scale.apply(1, 1_000_000);
+        assert!(faint(DensityScale::Linear) < faint(DensityScale::Sqrt));
+        assert!(faint(DensityScale::Sqrt) < faint(DensityScale::Log));
+        assert!(faint(DensityScale::Log) > 0.04);
+    }
+}
@@ -0,0 +1,157 @@
+// matter wave sources. the wavelength comes from de broglie, lambda = h / p,
+// and is thousands of times shorter than light's, so the slits are measured
+// in nanometers instead of micrometers and the screen gets shrunk down too
+
+use crate::component::{SlitStructure, Source};
+use crate::units::{Length, Wavelength};
+
+use super::spectrum::{VISIBLE_MAX, VISIBLE_MIN};
+
+// all SI
+const PLANCK: f64 = 6.626_070_15e-34;
+const SPEED_OF_LIGHT: f64 = 299_792_458.;
+const ELEMENTARY_CHARGE: f64 = 1.602_176_634e-19;
+const ATOMIC_MASS: f64 = 1.660_539_066_6e-27;
+const ELECTRON_MASS: f64 = 9.109_383_701_5e-31;
+const NEUTRON_MASS: f64 = 1.674_927_498_04e-27;
+const HELIUM_MASS: f64 = 4.002_602 * ATOMIC_MASS;
+const C60_MASS: f64 = 720.66 * ATOMIC_MASS;
+
+// the laser's default, matter waves at their usual speed get drawn like this
+pub const OPTICAL_REFERENCE: Wavelength = Wavelength::nanometers(500.);
+
+pub struct Species {
+    pub mass: f64, // kilograms
+    // charged ones get sped up through SlitStructure::voltage,
+    // neutral ones just go at SlitStructure::velocity
+    pub charged: bool,
+    // the voltage (kilovolts) or velocity (m/s) it usually goes at
+    pub usual_speed: f32,
+}
+
+/// what the source is made of, None for light
+pub fn species(source: &Source) -> Option<Species> {
+    match source {
+        Source::Electron => Some(Species {
+            mass: ELECTRON_MASS,
+            charged: true,
+            usual_speed: 50.,
+        }),
+        // thermal neutrons
+        Source::Neutron => Some(Species {
+            mass: NEUTRON_MASS,
+            charged: false,
+            usual_speed: 2200.,
+        }),
+        Source::Helium => Some(Species {
+            mass: HELIUM_MASS,
+            charged: false,
+            usual_speed: 1000.,
+        }),
+        // buckyballs, like arndt and zeilinger's
+        Source::C60 => Some(Species {
+            mass: C60_MASS,
+            charged: false,
+            usual_speed: 200.,
+        }),
+        _ => None,
+    }
+}
+
+/// de broglie wavelength at `speed`, kilovolts for charged species and m/s
+/// for the rest. relativistic, which only really matters for fast electrons
+pub fn de_broglie_wavelength(species: &Species, speed: f32) -> Wavelength {
+    let rest_energy = species.mass * SPEED_OF_LIGHT * SPEED_OF_LIGHT;
+
+    let momentum = if species.charged {
+        let kinetic = ELEMENTARY_CHARGE * speed as f64 * 1000.;
+        (kinetic * (kinetic + 2. * rest_energy)).sqrt() / SPEED_OF_LIGHT
+    } else {
+        let velocity = speed as f64;
+        let gamma = 1. / (1. - (velocity / SPEED_OF_LIGHT).powi(2)).sqrt();
+        gamma * species.mass * velocity
+    };
+
+    Wavelength::from_meters((PLANCK / momentum) as f32)
+}
+
+/// the source's wavelength if it's matter, None for light
+pub fn matter_wavelength(slit: &SlitStructure) -> Option<Wavelength> {
+    species(&slit.source).map(|species| {
+        let speed = if species.charged {
+            slit.voltage
+        } else {
+            slit.velocity
+        };
+        de_broglie_wavelength(&species, speed)
+    })
+}
+
+/// what `slit_width` and `separation` are counted in on the buttons
+pub fn slit_unit(slit: &SlitStructure) -> Length {
+    match species(&slit.source) {
+        Some(_) => Length::nanometers(1.),
+        None => Length::micrometers(1.),
+    }
+}
+
+/// how much smaller the screen is than for light. picked so that at its usual speed
+/// a species makes the same picture as the default laser, and speeding it up squeezes it
+pub fn screen_scale(slit: &SlitStructure) -> f32 {
+    match species(&slit.source) {
+        Some(species) => {
+            let usual = de_broglie_wavelength(&species, species.usual_speed);
+            (usual / OPTICAL_REFERENCE) * (Length::micrometers(1.) / slit_unit(slit))
+        }
+        None => 1.,
+    }
+}
+
+/// the light wavelength that would make the same picture as `wavelength`, for coloring
+/// things in. light is left alone, matter waves are kept to something visible
+pub fn display_wavelength(slit: &SlitStructure, wavelength: Wavelength) -> Wavelength {
+    match species(&slit.source) {
+        Some(_) => {
+            let optical =
+                wavelength * (Length::micrometers(1.) / slit_unit(slit) / screen_scale(slit));
+            Wavelength::nanometers(
+                optical
+                    .in_nanometers()
+                    .clamp(VISIBLE_MIN.in_nanometers(), VISIBLE_MAX.in_nanometers()),
+            )
+        }
+        None => wavelength,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn electrons_need_relativity() {
+        // 50 kv electrons, 5.36 pm with the correction and 5.48 pm without
+        let electron = species(&Source::Electron).unwrap();
+        let wavelength = de_broglie_wavelength(&electron, 50.).in_nanometers();
+        assert!((wavelength - 0.005355).abs() < 0.000005, "{wavelength}");
+    }
+
+    #[test]
+    fn thermal_neutrons() {
+        let neutron = species(&Source::Neutron).unwrap();
+        let wavelength = de_broglie_wavelength(&neutron, 2200.).in_nanometers();
+        assert!((wavelength - 0.1798).abs() < 0.0005, "{wavelength}");
+    }
+
+    #[test]
+    fn usual_speed_looks_like_the_laser() {
+        let slit = SlitStructure {
+            source: Source::C60,
+            velocity: species(&Source::C60).unwrap().usual_speed,
+            ..SlitStructure::default()
+        };
+        let wavelength = matter_wavelength(&slit).unwrap();
+        let optical = display_wavelength(&slit, wavelength);
+        assert!((optical - OPTICAL_REFERENCE).in_nanometers().abs() < 0.01);
+    }
+}
@@ -0,0 +1,903 @@
+// the actual double slit math lives here and only here.
+// no bevy types allowed! the particle sampler calls into this directly
+// and the light shader just gets a table of values computed from it,
+// so if the physics is wrong it's wrong in exactly one place :)
+
+use std::f32::consts::PI;
+
+use crate::component::{Coherence, Diffraction, Plate, SlitStructure};
+use crate::units::{Distance, Length, Wavelength};
+
+use self::aperture::FarField;
+use self::complex::Complex;
+use self::fresnel::fresnel_slit;
+use self::matter::{screen_scale, slit_unit};
+use self::polarization::{polarizer_transmission, source_states, through_slit, Jones};
+
+pub mod aperture;
+pub mod bohm;
+mod complex;
+pub mod detector;
+pub mod export;
+mod fft;
+mod fresnel;
+pub mod histogram;
+pub mod matter;
+pub mod polarization;
+pub mod sampling;
+pub mod spectrum;
+pub mod wave;
+
+// physical size of the far screen we're pretending to look at, for light.
+// matter waves get a smaller one, see screen_width
+pub const FULL_SCREEN_WIDTH: Distance = Distance::centimeters(40.);
+pub const FULL_SCREEN_HEIGHT: Distance = Distance::centimeters(20.);
+
+// slits are very tall compared to their width, this only matters for the y spread.
+// in matter::slit_unit, like the numbers on the slit width button
+pub const SLIT_HEIGHT: f32 = 5.;
+
+// past this fresnel number Diffraction::Auto switches over to the near field math
+pub const FRESNEL_NUMBER_LIMIT: f32 = 0.1;
+
+// how many points across a wide source get added up for aperture masks
+pub const SOURCE_POINTS: usize = 15;
+
+// how many points across the screen get sent to the light shader,
+// roughly one per pixel of the screen mesh
+pub const PROFILE_SAMPLES: usize = 512;
+// and how many up it, only 2d apertures vary in y
+pub const PROFILE_ROWS: usize = 128;
+
+/// sin(a) / a, with the hole at 0 patched up
+pub fn sinc(a: f32) -> f32 {
+    if a.abs() < 1e-6 {
+        1.
+    } else {
+        a.sin() / a
+    }
+}
+
+/// angle from the slits to a point `displacement` meters off center on the screen
+pub fn sine_theta(displacement: f32, screen_distance: Distance) -> f32 {
+    let screen_distance = screen_distance.meters();
+    displacement / (displacement * displacement + screen_distance * screen_distance).sqrt()
+}
+
+/// angle out from straight ahead to horizontal screen position `x` (0 to 1), radians.
+/// negative to the left
+pub fn screen_angle(slit: &SlitStructure, x: f32) -> f32 {
+    let displacement = (x - 0.5) * screen_width(slit).meters();
+    (displacement / slit.screen_distance.meters()).atan()
+}
+
+/// single slit envelope in the far field, peaks at 1
+pub fn single_slit(sine_theta: f32, slit_width: Length, wavelength: Wavelength) -> f32 {
+    let a = ((PI * slit_width.meters()) / wavelength.meters()) * sine_theta;
+    sinc(a) * sinc(a)
+}
+
+/// how wide the screen is
+pub fn screen_width(slit: &SlitStructure) -> Distance {
+    FULL_SCREEN_WIDTH * screen_scale(slit)
+}
+
+/// how tall the screen is
+pub fn screen_height(slit: &SlitStructure) -> Distance {
+    FULL_SCREEN_HEIGHT * screen_scale(slit)
+}
+
+/// where slit `index` sits relative to the middle of the grating
+pub fn slit_center(slit: &SlitStructure, index: u32) -> Length {
+    slit.separation * (index as f32 - (slit.slit_count as f32 - 1.) / 2.)
+}
+
+/// how wide slit `index` is
+pub fn width_of(slit: &SlitStructure, index: u32) -> Length {
+    slit.slit_width * slit.width_scales[index as usize]
+}
+
+/// amplitude slit `index` lets through straight ahead, compared to a normal slit.
+/// goes with the area since a wider slit catches more of the wave
+fn weight_of(slit: &SlitStructure, index: u32) -> f32 {
+    slit.transmissions[index as usize] * slit.width_scales[index as usize]
+}
+
+/// how much light gets through slit `index` altogether, compared to a normal slit.
+/// a wider slit lets more in but squeezes it into a narrower envelope
+pub fn slit_power(slit: &SlitStructure, index: u32) -> f32 {
+    let transmission = slit.transmissions[index as usize];
+    transmission
+        * transmission
+        * slit.width_scales[index as usize]
+        * polarizer_transmission(slit, index)
+}
+
+/// how far the plate holds the wave at `slit.plate_slit` back, radians, for one
+/// `wavelength`. glass adds (n - 1) t of extra path, so it holds short
+/// wavelengths back more. matter waves go through the same glass formula, which
+/// doesn't mean much but doesn't hurt either
+pub fn plate_phase(slit: &SlitStructure, wavelength: Wavelength) -> f32 {
+    match slit.plate {
+        Plate::None => 0.,
+        Plate::Phase => slit.plate_phase.to_radians(),
+        Plate::Glass => {
+            let extra_path = slit.plate_thickness * (slit.plate_index - 1.);
+            2. * PI * extra_path.meters() / wavelength.meters()
+        }
+    }
+}
+
+/// how far across the screen (as a fraction of its width) the fringe that was
+/// straight ahead moves with the plate in. it heads toward the plated slit, to where
+/// that slit and its neighbour are back in step. exact for two slits, with more
+/// it's only a rough guide to how the pattern slides
+pub fn fringe_shift(slit: &SlitStructure, wavelength: Wavelength) -> f32 {
+    // the middle slit of an odd grating pulls both ways at once
+    let center = slit_center(slit, slit.plate_slit).meters();
+    if center == 0. || slit.covered[slit.plate_slit as usize] {
+        return 0.;
+    }
+
+    // the same fringe comes round again every 2 pi, so the nearest one will do
+    let phase = (plate_phase(slit, wavelength) + PI).rem_euclid(2. * PI) - PI;
+    let sine_theta =
+        (phase / (2. * PI) * (wavelength.meters() / slit.separation.meters())).clamp(-1., 1.);
+    let tan_theta = sine_theta / (1. - sine_theta * sine_theta).sqrt();
+
+    center.signum() * tan_theta * (slit.screen_distance / screen_width(slit))
+}
+
+/// a^2 / (L lambda), a being half the width of the whole grating.
+/// way under 1 and the far field (fraunhofer) math is fine
+pub fn fresnel_number(slit: &SlitStructure, wavelength: Wavelength) -> f32 {
+    let half_width =
+        (slit.separation * (slit.slit_count - 1) as f32 + slit.slit_width).meters() / 2.;
+
+    half_width * half_width / (slit.screen_distance.meters() * wavelength.meters())
+}
+
+/// whether the screen is close enough that we need the fresnel integrals
+pub fn is_near_field(slit: &SlitStructure, wavelength: Wavelength) -> bool {
+    match slit.diffraction {
+        Diffraction::Auto => fresnel_number(slit, wavelength) > FRESNEL_NUMBER_LIMIT,
+        Diffraction::Fraunhofer => false,
+        Diffraction::Fresnel => true,
+    }
+}
+
+/// complex degree of coherence between two points `separation` apart on the slits,
+/// lit by a uniform incoherent source `slit.source_width` wide (van cittert-zernike).
+/// 1 for a point source, and it hits 0 once the source is wide enough to wash out the fringes
+pub fn degree_of_coherence(
+    slit: &SlitStructure,
+    separation: Length,
+    wavelength: Wavelength,
+) -> f32 {
+    let source_width = slit.source_width.meters();
+    let source_distance = slit.source_distance.meters();
+    sinc(PI * source_width * separation.meters() / (wavelength.meters() * source_distance))
+}
+
+/// where the pattern from each point across the source lands, as shifts in
+/// screen position (0 to 1). just the one at 0 for a point source
+fn source_shifts(slit: &SlitStructure) -> Vec<f32> {
+    if slit.source_width == Length::default() {
+        return vec![0.];
+    }
+
+    // light from a point u off to the side comes through tilted by u / R
+    let magnification = slit.screen_distance / slit.source_distance;
+    (0..SOURCE_POINTS)
+        .map(|i| {
+            let u = slit.source_width * ((i as f32 + 0.5) / SOURCE_POINTS as f32 - 0.5);
+            u.meters() * magnification / screen_width(slit).meters()
+        })
+        .collect()
+}
+
+/// far field amplitude from each of `slits`, `sine_theta` out from the middle of the grating
+fn fraunhofer_amplitudes(
+    slit: &SlitStructure,
+    sine_theta: f32,
+    wavelength: Wavelength,
+    slits: &[u32],
+) -> Vec<Complex> {
+    let separation = slit.separation.meters();
+    let wavelength = wavelength.meters();
+
+    // phase difference between neighbouring slits. the pattern repeats every 2 pi,
+    // and f32 gets pretty rough if we let it grow into the thousands
+    let delta = ((2. * PI * separation) / wavelength) * sine_theta;
+    let delta = delta - 2. * PI * (delta / (2. * PI)).round();
+
+    let center = (slit.slit_count as f32 - 1.) / 2.;
+    slits
+        .iter()
+        .map(|&i| {
+            let slit_width = width_of(slit, i).meters();
+            let envelope = sinc(((PI * slit_width) / wavelength) * sine_theta);
+            // slits further from where we're looking are further behind, like the fresnel math
+            Complex::from_phase(-(i as f32 - center) * delta) * (envelope * weight_of(slit, i))
+        })
+        .collect()
+}
+
+/// near field amplitude from each of `slits` at `displacement` meters along the screen
+fn fresnel_amplitudes(
+    slit: &SlitStructure,
+    displacement: f32,
+    wavelength: Wavelength,
+    slits: &[u32],
+) -> Vec<Complex> {
+    let wavelength = wavelength.meters() as f64;
+    let screen_distance = slit.screen_distance.meters() as f64;
+
+    slits
+        .iter()
+        .map(|&i| {
+            let center = slit_center(slit, i).meters() as f64;
+            let half_width = (width_of(slit, i).meters() / 2.) as f64;
+            fresnel_slit(
+                displacement as f64,
+                center - half_width,
+                center + half_width,
+                wavelength,
+                screen_distance,
+            ) * weight_of(slit, i)
+        })
+        .collect()
+}
+
+/// amplitude from each of `slits` at horizontal screen position `x`, near or far field
+fn amplitudes(slit: &SlitStructure, x: f32, wavelength: Wavelength, slits: &[u32]) -> Vec<Complex> {
+    let displacement = (x - 0.5) * screen_width(slit).meters();
+
+    let amplitudes = if is_near_field(slit, wavelength) {
+        fresnel_amplitudes(slit, displacement, wavelength, slits)
+    } else {
+        fraunhofer_amplitudes(
+            slit,
+            sine_theta(displacement, slit.screen_distance),
+            wavelength,
+            slits,
+        )
+    };
+
+    // the plate comes on top of whatever the slit does on its own
+    let delay = Complex::from_phase(plate_phase(slit, wavelength));
+    amplitudes
+        .into_iter()
+        .zip(slits)
+        .map(|(amplitude, &i)| {
+            if i == slit.plate_slit {
+                amplitude * delay
+            } else {
+                amplitude
+            }
+        })
+        .collect()
+}
+
+/// intensity at horizontal screen position `x` (0 to 1, left to right) for light of
+/// one `wavelength`, between 0 and 1.
+/// near field edge ringing can poke a little over 1
+pub fn intensity_x(slit: &SlitStructure, x: f32, wavelength: Wavelength) -> f32 {
+    intensity_x_as(slit, x, wavelength, &slit.coherence, slit.which_path)
+}
+
+/// what `intensity_x` would be with the which path detector off. the particles it
+/// doesn't catch land like this, the ones it does go by `tagged_intensity_x`
+pub fn untagged_intensity_x(slit: &SlitStructure, x: f32, wavelength: Wavelength) -> f32 {
+    intensity_x_as(slit, x, wavelength, &slit.coherence, 0.)
+}
+
+/// what `intensity_x` would be if the slits didn't interfere with each other at all.
+/// for identical slits that's the single slit envelope the fringes sit under
+pub fn envelope_x(slit: &SlitStructure, x: f32, wavelength: Wavelength) -> f32 {
+    intensity_x_as(slit, x, wavelength, &Coherence::Incoherent, 0.)
+}
+
+// intensity_x, with the slits adding up as `coherence` says and the which path
+// detector looking as hard as `which_path`
+fn intensity_x_as(
+    slit: &SlitStructure,
+    x: f32,
+    wavelength: Wavelength,
+    coherence: &Coherence,
+    which_path: f32,
+) -> f32 {
+    let open_slits: Vec<u32> = slit.open_slits().collect();
+    let amplitudes = amplitudes(slit, x, wavelength, &open_slits);
+
+    // everything lined up straight ahead, what it takes to get to 1 there
+    let total: f32 = open_slits.iter().map(|&i| weight_of(slit, i)).sum();
+    if total == 0. {
+        return 0.;
+    }
+
+    // each pure state the source puts out goes through on its own, and what's left of it
+    // behind each slit's polarizer and the analyzer only interferes as much as it lines up
+    let states: Vec<(Vec<Jones>, f32)> = source_states(slit)
+        .iter()
+        .map(|(state, share)| {
+            let through = open_slits
+                .iter()
+                .map(|&i| through_slit(slit, i, state))
+                .collect();
+            (through, *share)
+        })
+        .collect();
+
+    // the polarizers dim everything, scale it back up so it's the fringes that change
+    let squares: f32 = open_slits
+        .iter()
+        .map(|&i| weight_of(slit, i) * weight_of(slit, i))
+        .sum();
+    let passed: f32 = open_slits
+        .iter()
+        .map(|&i| weight_of(slit, i) * weight_of(slit, i) * polarizer_transmission(slit, i))
+        .sum::<f32>()
+        / squares;
+    if passed == 0. {
+        return 0.;
+    }
+
+    match coherence {
+        // with every slit open and a point source this works out to
+        // sin(N b)^2 / (N sin(b))^2 times the envelope, the usual cos^2 for two slits.
+        // a wide source only partly lines up the light at slits far apart,
+        // so each pair only interferes as much as it's coherent.
+        // the which path detector mixes in the incoherent sum on top of that
+        Coherence::Coherent => {
+            let coherence: Vec<f32> = (0..slit.slit_count)
+                .map(|k| {
+                    let gamma = degree_of_coherence(slit, slit.separation * k as f32, wavelength);
+                    if k == 0 {
+                        gamma
+                    } else {
+                        gamma * (1. - which_path)
+                    }
+                })
+                .collect();
+
+            let mut sum = 0.;
+            for (through, share) in &states {
+                for (k, i) in open_slits.iter().enumerate() {
+                    for (l, j) in open_slits.iter().enumerate() {
+                        let overlap = through[k].dot(&through[l]);
+                        sum += share
+                            * (amplitudes[k] * amplitudes[l].conj() * overlap).re
+                            * coherence[i.abs_diff(*j) as usize];
+                    }
+                }
+            }
+            sum.max(0.) / (total * total * passed)
+        }
+        // as if each slit was opened on its own and the results piled up
+        Coherence::Incoherent => {
+            let mut sum = 0.;
+            for (through, share) in &states {
+                for (amplitude, through) in amplitudes.iter().zip(through) {
+                    sum += share * amplitude.norm_sqr() * through.norm_sqr();
+                }
+            }
+            sum / (squares * passed)
+        }
+    }
+}
+
+/// intensity at horizontal screen position `x` from slit `index` alone, for particles
+/// the which path detector caught going through it. between 0 and 1 like `intensity_x`
+pub fn tagged_intensity_x(slit: &SlitStructure, x: f32, wavelength: Wavelength, index: u32) -> f32 {
+    let weight = weight_of(slit, index);
+    if weight == 0. {
+        return 0.;
+    }
+    amplitudes(slit, x, wavelength, &[index])[0].norm_sqr() / (weight * weight)
+}
+
+/// intensity at vertical screen position `y` (0 to 1, bottom to top), between 0 and 1
+pub fn intensity_y(slit: &SlitStructure, y: f32, wavelength: Wavelength) -> f32 {
+    let displacement = (y - 0.5) * screen_height(slit).meters();
+    let sine_theta = sine_theta(displacement, slit.screen_distance);
+
+    single_slit(sine_theta, slit_unit(slit) * SLIT_HEIGHT, wavelength)
+}
+
+/// intensity at screen position (`x`, `y`) for one `wavelength`, from the aperture's
+/// far field if there is one and `intensity_x` if not. `y` only matters for 2d apertures.
+/// masks don't know about their slits, so a wide source there is the same
+/// pattern smeared across the screen, one copy per point of the source
+pub fn intensity(
+    slit: &SlitStructure,
+    far_field: Option<&FarField>,
+    x: f32,
+    y: f32,
+    wavelength: Wavelength,
+) -> f32 {
+    match far_field {
+        Some(far_field) => {
+            let shifts = source_shifts(slit);
+            shifts
+                .iter()
+                .map(|shift| far_field.intensity_at(slit, x + shift, y, wavelength))
+                .sum::<f32>()
+                / shifts.len() as f32
+        }
+        None => intensity_x(slit, x, wavelength),
+    }
+}
+
+/// `intensity` across the middle of the screen at PROFILE_SAMPLES evenly spaced points,
+/// each line of the spectrum added in by how bright it is so it all still peaks at about 1
+pub fn profile(slit: &SlitStructure, far_field: Option<&FarField>) -> Vec<f32> {
+    let lines = spectrum::spectrum(slit);
+    (0..PROFILE_SAMPLES)
+        .map(|i| {
+            let x = i as f32 / (PROFILE_SAMPLES - 1) as f32;
+            lines
+                .iter()
+                .map(|line| line.weight * intensity(slit, far_field, x, 0.5, line.wavelength))
+                .sum()
+        })
+        .collect()
+}
+
+/// the pattern sampled on a grid over the screen, what the light shader draws
+pub struct IntensityTable {
+    pub columns: usize,
+    pub rows: usize,
+    // row major starting from the bottom, sample (i, j) sits at
+    // x = i / (columns - 1), y = j / (rows - 1)
+    pub values: Vec<f32>,
+}
+
+/// `intensity` for one `wavelength` sampled across the screen.
+/// one row unless the aperture is 2d
+pub fn intensity_table(
+    slit: &SlitStructure,
+    far_field: Option<&FarField>,
+    wavelength: Wavelength,
+) -> IntensityTable {
+    let rows = match far_field {
+        Some(far_field) if far_field.is_2d() => PROFILE_ROWS,
+        _ => 1,
+    };
+
+    let mut values = Vec::with_capacity(PROFILE_SAMPLES * rows);
+    for j in 0..rows {
+        let y = if rows == 1 {
+            0.5
+        } else {
+            j as f32 / (rows - 1) as f32
+        };
+        for i in 0..PROFILE_SAMPLES {
+            let x = i as f32 / (PROFILE_SAMPLES - 1) as f32;
+            values.push(intensity(slit, far_field, x, y, wavelength));
+        }
+    }
+
+    IntensityTable {
+        columns: PROFILE_SAMPLES,
+        rows,
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Polarization, SlitControl, MAX_SLIT_COUNT};
+
+    const EPSILON: f32 = 1e-4;
+
+    // screen position (0 to 1) at which light leaves the slits at angle theta
+    fn x_at(slit: &SlitStructure, sine_theta: f32) -> f32 {
+        let tan_theta = sine_theta / (1. - sine_theta * sine_theta).sqrt();
+        0.5 + tan_theta * (slit.screen_distance / FULL_SCREEN_WIDTH)
+    }
+
+    #[test]
+    fn center_is_brightest() {
+        let slit = SlitStructure::default();
+        assert!((intensity_x(&slit, 0.5, slit.wavelength) - 1.).abs() < EPSILON);
+        assert!((intensity_y(&slit, 0.5, slit.wavelength) - 1.).abs() < EPSILON);
+    }
+
+    #[test]
+    fn dark_fringe_at_half_wavelength_path_difference() {
+        // d sin(theta) = lambda / 2
+        let slit = SlitStructure::default();
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        assert!(intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) < EPSILON);
+    }
+
+    #[test]
+    fn bright_fringe_follows_envelope() {
+        // d sin(theta) = lambda, so only the single slit envelope is left
+        let slit = SlitStructure::default();
+        let sine_theta = slit.wavelength.meters() / slit.separation.meters();
+        let a = PI * (slit.slit_width / slit.separation);
+        let expected = (a.sin() / a) * (a.sin() / a);
+        assert!(
+            (intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) - expected).abs()
+                < EPSILON
+        );
+    }
+
+    #[test]
+    fn envelope_zero_at_first_single_slit_minimum() {
+        // a sin(theta) = lambda
+        let slit = SlitStructure {
+            slit_width: Length::micrometers(10.),
+            ..SlitStructure::default()
+        };
+        let sine_theta = slit.wavelength.meters() / slit.slit_width.meters();
+        assert!(intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) < EPSILON);
+    }
+
+    #[test]
+    fn grating_minima_between_principal_maxima() {
+        // N slits go dark at d sin(theta) = lambda / N
+        let slit = SlitStructure {
+            slit_count: 5,
+            diffraction: Diffraction::Fraunhofer,
+            ..SlitStructure::default()
+        };
+        let sine_theta = slit.wavelength.meters() / (5. * slit.separation.meters());
+        assert!(intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) < EPSILON);
+    }
+
+    #[test]
+    fn grating_principal_maxima_match_double_slit() {
+        let double = SlitStructure::default();
+        let grating = SlitStructure {
+            slit_count: 12,
+            diffraction: Diffraction::Fraunhofer,
+            ..SlitStructure::default()
+        };
+        let sine_theta = double.wavelength.meters() / double.separation.meters();
+        let x = x_at(&double, sine_theta);
+        assert!(
+            (intensity_x(&double, x, double.wavelength)
+                - intensity_x(&grating, x, grating.wavelength))
+            .abs()
+                < EPSILON
+        );
+    }
+
+    #[test]
+    fn one_slit_is_just_the_envelope() {
+        let slit = SlitStructure {
+            slit_count: 1,
+            ..SlitStructure::default()
+        };
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        let expected = single_slit(sine_theta, slit.slit_width, slit.wavelength);
+        assert!(
+            (intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) - expected).abs()
+                < EPSILON
+        );
+    }
+
+    #[test]
+    fn covering_a_slit_leaves_the_envelope() {
+        let mut slit = SlitStructure::default();
+        slit.toggle_covered(1);
+
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        let expected = single_slit(sine_theta, slit.slit_width, slit.wavelength);
+        assert!(
+            (intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) - expected).abs()
+                < EPSILON
+        );
+    }
+
+    #[test]
+    fn covering_every_slit_is_dark() {
+        let mut slit = SlitStructure::default();
+        slit.toggle_covered(0);
+        slit.toggle_covered(1);
+        assert!(intensity_x(&slit, 0.5, slit.wavelength) < EPSILON);
+    }
+
+    #[test]
+    fn incoherent_slits_have_no_fringes() {
+        let slit = SlitStructure {
+            coherence: Coherence::Incoherent,
+            ..SlitStructure::default()
+        };
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        assert!(intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) > 0.9);
+    }
+
+    #[test]
+    fn point_source_is_fully_coherent() {
+        let slit = SlitStructure::default();
+        assert!(
+            (degree_of_coherence(&slit, slit.separation, slit.wavelength) - 1.).abs() < EPSILON
+        );
+    }
+
+    #[test]
+    fn wide_source_washes_out_fringes() {
+        // first zero of the degree of coherence, s d / (lambda R) = 1
+        let mut slit = SlitStructure::default();
+        slit.source_width = Length::nanometers(
+            slit.wavelength.in_nanometers() * slit.source_distance.meters()
+                / slit.separation.meters(),
+        );
+        assert!(degree_of_coherence(&slit, slit.separation, slit.wavelength).abs() < EPSILON);
+
+        // the dark fringe fills in halfway up the envelope, same as the bright ones come down
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        let expected = single_slit(sine_theta, slit.slit_width, slit.wavelength) / 2.;
+        let x = x_at(&slit, sine_theta);
+        assert!((intensity_x(&slit, x, slit.wavelength) - expected).abs() < EPSILON);
+    }
+
+    #[test]
+    fn full_which_path_is_incoherent() {
+        let watched = SlitStructure {
+            which_path: 1.,
+            ..SlitStructure::default()
+        };
+        let sine_theta = watched.wavelength.meters() / (2. * watched.separation.meters());
+        let x = x_at(&watched, sine_theta);
+
+        // same as the slits on their own, averaged
+        let expected = (tagged_intensity_x(&watched, x, watched.wavelength, 0)
+            + tagged_intensity_x(&watched, x, watched.wavelength, 1))
+            / 4.;
+        assert!((intensity_x(&watched, x, watched.wavelength) - expected).abs() < EPSILON);
+        assert!(expected > 0.1);
+    }
+
+    #[test]
+    fn half_which_path_is_half_the_fringes() {
+        let slit = SlitStructure::default();
+        let watched = SlitStructure {
+            which_path: 0.5,
+            ..SlitStructure::default()
+        };
+        // on a bright fringe coherent is twice incoherent, so half way is 3/4
+        assert!((intensity_x(&watched, 0.5, watched.wavelength) - 0.75).abs() < EPSILON);
+        assert!((intensity_x(&slit, 0.5, slit.wavelength) - 1.).abs() < EPSILON);
+    }
+
+    #[test]
+    fn untagged_and_tagged_add_up_to_the_light() {
+        // the particles split the same way the light does: the ones that get by the
+        // detector keep all their fringes, the caught ones have none
+        let watched = SlitStructure {
+            which_path: 0.5,
+            ..SlitStructure::default()
+        };
+        let wavelength = watched.wavelength;
+        for x in [0.3, 0.45, 0.5, 0.52, 0.7] {
+            let tagged = (tagged_intensity_x(&watched, x, wavelength, 0)
+                + tagged_intensity_x(&watched, x, wavelength, 1))
+                / 4.;
+            let mixed = 0.5 * untagged_intensity_x(&watched, x, wavelength) + 0.5 * tagged;
+            assert!((intensity_x(&watched, x, wavelength) - mixed).abs() < EPSILON);
+        }
+        assert!(
+            (untagged_intensity_x(&watched, 0.5, wavelength)
+                - intensity_x(&SlitStructure::default(), 0.5, wavelength))
+            .abs()
+                < EPSILON
+        );
+    }
+
+    #[test]
+    fn unequal_slits_lower_the_contrast() {
+        let mut slit = SlitStructure::default();
+        slit.transmissions[1] = 0.5;
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        let envelope = single_slit(sine_theta, slit.slit_width, slit.wavelength);
+
+        // still bright in the middle, but the dark fringe only gets down to
+        // (1 - 0.5)^2 / (1 + 0.5)^2 of the envelope, a visibility of 2 t1 t2 / (t1^2 + t2^2)
+        assert!((intensity_x(&slit, 0.5, slit.wavelength) - 1.).abs() < EPSILON);
+        let dark = intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength);
+        assert!((dark - envelope / 9.).abs() < EPSILON);
+        assert!(dark > 0.01);
+    }
+
+    #[test]
+    fn dimming_every_slit_keeps_the_pattern() {
+        let slit = SlitStructure::default();
+        let dimmed = SlitStructure {
+            transmissions: [0.3; MAX_SLIT_COUNT as usize],
+            ..SlitStructure::default()
+        };
+        for x in [0.48, 0.5, 0.505, 0.52] {
+            assert!(
+                (intensity_x(&slit, x, slit.wavelength)
+                    - intensity_x(&dimmed, x, dimmed.wavelength))
+                .abs()
+                    < EPSILON
+            );
+        }
+    }
+
+    #[test]
+    fn half_wave_plate_darkens_the_middle() {
+        let slit = SlitStructure {
+            plate: Plate::Phase,
+            plate_phase: 180.,
+            ..SlitStructure::default()
+        };
+        assert!(intensity_x(&slit, 0.5, slit.wavelength) < EPSILON);
+    }
+
+    #[test]
+    fn fringes_slide_toward_the_plate() {
+        // 0.15 micrometers of extra path, just under a third of a wave at 500 nm
+        let slit = SlitStructure {
+            plate: Plate::Glass,
+            plate_slit: 1,
+            plate_thickness: Length::micrometers(0.3),
+            ..SlitStructure::default()
+        };
+        let shift = fringe_shift(&slit, slit.wavelength);
+        assert!(shift > 0.);
+        let sine_theta = sine_theta(shift * screen_width(&slit).meters(), slit.screen_distance);
+        let envelope = single_slit(sine_theta, slit.slit_width, slit.wavelength);
+        assert!((intensity_x(&slit, 0.5 + shift, slit.wavelength) - envelope).abs() < EPSILON);
+
+        let quarter = SlitStructure {
+            plate: Plate::Phase,
+            plate_phase: 90.,
+            plate_slit: 1,
+            ..SlitStructure::default()
+        };
+        let shift = fringe_shift(&quarter, quarter.wavelength);
+        assert!(shift > 0.);
+        assert!(
+            intensity_x(&quarter, 0.5 + shift, quarter.wavelength)
+                > intensity_x(&quarter, 0.5 - shift, quarter.wavelength)
+        );
+    }
+
+    #[test]
+    fn crossed_polarizers_kill_the_fringes() {
+        let mut slit = SlitStructure::default();
+        slit.add_val(&SlitControl::Eraser, 0.);
+        assert!(slit.is_eraser());
+
+        // the dark fringe comes up to meet the bright one, half the envelope each
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        let envelope = single_slit(sine_theta, slit.slit_width, slit.wavelength);
+        let x = x_at(&slit, sine_theta);
+        assert!((intensity_x(&slit, x, slit.wavelength) - envelope / 2.).abs() < EPSILON);
+        assert!((intensity_x(&slit, 0.5, slit.wavelength) - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn diagonal_analyzer_erases_which_path() {
+        let mut slit = SlitStructure::default();
+        slit.add_val(&SlitControl::Eraser, 0.);
+        slit.analyzer = Some(45.);
+
+        let sine_theta = slit.wavelength.meters() / (2. * slit.separation.meters());
+        assert!(intensity_x(&slit, x_at(&slit, sine_theta), slit.wavelength) < EPSILON);
+        assert!((intensity_x(&slit, 0.5, slit.wavelength) - 1.).abs() < EPSILON);
+
+        // unpolarized light never had both slits in the same state to begin with
+        slit.polarization = Polarization::Unpolarized;
+        assert!((intensity_x(&slit, 0.5, slit.wavelength) - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn fresnel_agrees_with_fraunhofer_far_away() {
+        // default double slit has a fresnel number of ~0.002
+        let far = SlitStructure {
+            diffraction: Diffraction::Fraunhofer,
+            ..SlitStructure::default()
+        };
+        let near = SlitStructure {
+            diffraction: Diffraction::Fresnel,
+            ..SlitStructure::default()
+        };
+        for x in [0.48, 0.5, 0.505, 0.52, 0.55] {
+            assert!(
+                (intensity_x(&far, x, far.wavelength) - intensity_x(&near, x, near.wavelength))
+                    .abs()
+                    < 0.02
+            );
+        }
+    }
+
+    #[test]
+    fn auto_picks_near_field_for_wide_gratings_up_close() {
+        let slit = SlitStructure::default();
+        assert!(!is_near_field(&slit, slit.wavelength));
+
+        let slit = SlitStructure {
+            slit_count: 20,
+            separation: Length::micrometers(100.),
+            screen_distance: Distance::centimeters(20.),
+            ..SlitStructure::default()
+        };
+        assert!(fresnel_number(&slit, slit.wavelength) > FRESNEL_NUMBER_LIMIT);
+        assert!(is_near_field(&slit, slit.wavelength));
+    }
+
+    #[test]
+    fn matter_waves_at_usual_speed_look_like_the_laser() {
+        let light = SlitStructure::default();
+        // same numbers, nanometers instead of micrometers
+        let electrons = SlitStructure {
+            source: crate::component::Source::Electron,
+            separation: Length::nanometers(50.),
+            slit_width: Length::nanometers(5.),
+            ..SlitStructure::default()
+        };
+        let wavelength = matter::matter_wavelength(&electrons).unwrap();
+        for x in [0.5, 0.505, 0.51, 0.52] {
+            let expected = intensity_x(&light, x, light.wavelength);
+            assert!((intensity_x(&electrons, x, wavelength) - expected).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn pattern_is_symmetric() {
+        let slit = SlitStructure::default();
+        for x in [0.1, 0.27, 0.4, 0.49] {
+            assert!(
+                (intensity_x(&slit, x, slit.wavelength)
+                    - intensity_x(&slit, 1. - x, slit.wavelength))
+                .abs()
+                    < EPSILON
+            );
+        }
+    }
+
+    #[test]
+    fn table_matches_intensity() {
+        let slit = SlitStructure::default();
+        let table = intensity_table(&slit, None, slit.wavelength);
+        assert_eq!(table.rows, 1);
+        assert!((table.values[0] - intensity_x(&slit, 0., slit.wavelength)).abs() < EPSILON);
+        assert!(
+            (table.values[PROFILE_SAMPLES - 1] - intensity_x(&slit, 1., slit.wavelength)).abs()
+                < EPSILON
+        );
+    }
+
+    #[test]
+    fn fringes_sit_under_the_envelope() {
+        let slit = SlitStructure::default();
+        for i in 0..=100 {
+            let x = i as f32 / 100.;
+            let envelope = envelope_x(&slit, x, slit.wavelength);
+            assert!(intensity_x(&slit, x, slit.wavelength) <= envelope + EPSILON);
+
+            // two identical slits, so it's the single slit pattern
+            let displacement = (x - 0.5) * screen_width(&slit).meters();
+            let sine = sine_theta(displacement, slit.screen_distance);
+            assert!(
+                (envelope - single_slit(sine, slit.slit_width, slit.wavelength)).abs() < EPSILON
+            );
+        }
+    }
+
+    #[test]
+    fn ruler_backs_out_the_wavelength() {
+        // what a student does: put the cursors on the middle fringe and the next one over
+        let slit = SlitStructure::default();
+        let first = x_at(&slit, slit.wavelength.meters() / slit.separation.meters());
+        let angle = screen_angle(&slit, first) - screen_angle(&slit, 0.5);
+
+        let wavelength = slit.separation.meters() * angle.sin();
+        assert!((wavelength / slit.wavelength.meters() - 1.).abs() < 1e-3);
+
+        // and the spacing on the screen is the usual lambda L / d, give or take small angles
+        let spacing = (first - 0.5) * screen_width(&slit).meters();
+        let expected =
+            slit.wavelength.meters() * slit.screen_distance.meters() / slit.separation.meters();
+        assert!((spacing / expected - 1.).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,97 @@
+// polarization as jones vectors, two complex components (horizontal, vertical).
+// light from slits with crossed polarizers can't interfere, since the two
+// parts never line up, and an analyzer at 45 degrees after them projects
+// both back onto the same direction so the fringes come back. the quantum eraser
+
+use crate::component::{Polarization, SlitStructure};
+
+use super::complex::Complex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Jones(pub [Complex; 2]);
+
+impl Jones {
+    /// linear polarization `angle` degrees up from horizontal
+    pub fn linear(angle: f32) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Jones([Complex::new(cos, 0.), Complex::new(sin, 0.)])
+    }
+
+    /// right hand circular
+    pub fn circular() -> Self {
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        Jones([Complex::new(half, 0.), Complex::new(0., half)])
+    }
+
+    /// self . conj(other), how much two beams line up. 0 for orthogonal ones
+    pub fn dot(&self, other: &Jones) -> Complex {
+        self.0[0] * other.0[0].conj() + self.0[1] * other.0[1].conj()
+    }
+
+    /// how bright it is, 1 for any of the pure states
+    pub fn norm_sqr(&self) -> f32 {
+        self.0[0].norm_sqr() + self.0[1].norm_sqr()
+    }
+
+    /// what gets past an ideal linear polarizer at `angle` degrees
+    pub fn through_polarizer(&self, angle: f32) -> Jones {
+        let axis = Jones::linear(angle);
+        let along = self.dot(&axis);
+        Jones([axis.0[0] * along, axis.0[1] * along])
+    }
+}
+
+/// the pure states the source is a mix of, each with its share of the light.
+/// unpolarized light is half horizontal and half vertical, never interfering with each other
+pub fn source_states(slit: &SlitStructure) -> Vec<(Jones, f32)> {
+    match slit.polarization {
+        Polarization::Unpolarized => vec![(Jones::linear(0.), 0.5), (Jones::linear(90.), 0.5)],
+        Polarization::Horizontal => vec![(Jones::linear(0.), 1.)],
+        Polarization::Diagonal => vec![(Jones::linear(45.), 1.)],
+        Polarization::Vertical => vec![(Jones::linear(90.), 1.)],
+        Polarization::Circular => vec![(Jones::circular(), 1.)],
+    }
+}
+
+/// what `state` from the source looks like by the time it gets from slit `index`
+/// to the screen, after that slit's polarizer and the analyzer
+pub fn through_slit(slit: &SlitStructure, index: u32, state: &Jones) -> Jones {
+    let mut state = *state;
+    if let Some(angle) = slit.polarizers[index as usize] {
+        state = state.through_polarizer(angle);
+    }
+    if let Some(angle) = slit.analyzer {
+        state = state.through_polarizer(angle);
+    }
+    state
+}
+
+/// the share of the source's light the polarizers let through from slit `index`
+pub fn polarizer_transmission(slit: &SlitStructure, index: u32) -> f32 {
+    source_states(slit)
+        .iter()
+        .map(|(state, share)| share * through_slit(slit, index, state).norm_sqr())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossed_polarizers_are_orthogonal() {
+        let source = Jones::linear(45.);
+        let horizontal = source.through_polarizer(0.);
+        let vertical = source.through_polarizer(90.);
+        assert!(horizontal.dot(&vertical).norm_sqr() < 1e-10);
+        assert!((horizontal.norm_sqr() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unpolarized_light_loses_half() {
+        let mut slit = SlitStructure::default();
+        slit.polarizers[0] = Some(30.);
+        assert!((polarizer_transmission(&slit, 0) - 0.5).abs() < 1e-6);
+        assert!((polarizer_transmission(&slit, 1) - 1.).abs() < 1e-6);
+    }
+}
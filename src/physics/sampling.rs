@@ -0,0 +1,458 @@
+// where the particles land. the pattern for each line of the spectrum gets tabulated
+// and summed up once, whenever the setup changes, and then every particle is just a
+// binary search into those sums. a narrow pattern costs the same as a wide one,
+// and a screen that's dark everywhere gives nothing instead of looping forever
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::component::SlitStructure;
+
+use super::aperture::FarField;
+use super::spectrum::{pick_line, spectrum, SpectralLine};
+use super::{
+    intensity_table, intensity_y, slit_power, tagged_intensity_x, untagged_intensity_x,
+    PROFILE_SAMPLES,
+};
+
+/// cumulative distribution over 0 to 1, split into equal cells
+/// with the probability spread evenly inside each one
+#[derive(Debug, Clone)]
+pub struct Cdf {
+    // running total at the end of each cell, the last one is 1
+    cumulative: Vec<f32>,
+}
+
+impl Cdf {
+    /// from how much of the total lands in each cell. None if nothing does
+    pub fn from_cells(masses: impl IntoIterator<Item = f32>) -> Option<Self> {
+        let mut total = 0.;
+        let mut cumulative: Vec<f32> = masses
+            .into_iter()
+            .map(|mass| {
+                // near field ringing can dip a hair under 0
+                total += mass.max(0.);
+                total
+            })
+            .collect();
+
+        if total <= 0. || !total.is_finite() {
+            return None;
+        }
+        for value in cumulative.iter_mut() {
+            *value /= total;
+        }
+        Some(Cdf { cumulative })
+    }
+
+    /// from intensities sampled at evenly spaced points, the first at 0 and the last at 1.
+    /// each cell between two points gets their average
+    pub fn from_samples(values: &[f32]) -> Option<Self> {
+        Cdf::from_cells(values.windows(2).map(|pair| (pair[0] + pair[1]) / 2.))
+    }
+
+    /// where `u` (0 to 1) lands, 0 to 1
+    pub fn sample(&self, u: f32) -> f32 {
+        let cells = self.cumulative.len();
+        // first cell whose running total gets past u, empty cells never do.
+        // u = 1 gets past all of them, so it goes to the first one that reaches the end
+        let cell = match self.cumulative.partition_point(|&total| total <= u) {
+            past if past == cells => self.cumulative.partition_point(|&total| total < 1.),
+            cell => cell,
+        }
+        .min(cells - 1);
+
+        let start = if cell == 0 {
+            0.
+        } else {
+            self.cumulative[cell - 1]
+        };
+        let mass = self.cumulative[cell] - start;
+        let within = if mass > 0. {
+            ((u - start) / mass).clamp(0., 1.)
+        } else {
+            0.5
+        };
+
+        (cell as f32 + within) / cells as f32
+    }
+
+    /// how much of it lies below `x` (0 to 1), the other way around from `sample`
+    pub fn below(&self, x: f32) -> f32 {
+        let cells = self.cumulative.len();
+        let position = (x * cells as f32).clamp(0., cells as f32);
+        let cell = (position as usize).min(cells - 1);
+
+        let start = if cell == 0 {
+            0.
+        } else {
+            self.cumulative[cell - 1]
+        };
+        start + (self.cumulative[cell] - start) * (position - cell as f32)
+    }
+}
+
+/// cumulative distribution over the unit square, for patterns that don't split
+/// into an x part and a y part. picks a row, then a spot along it
+#[derive(Debug, Clone)]
+pub struct Cdf2d {
+    rows: Cdf,
+    // one per cell between two rows of samples, None for the dark ones
+    columns: Vec<Option<Cdf>>,
+}
+
+impl Cdf2d {
+    /// from `rows` rows of `columns` evenly spaced samples, row major from the bottom
+    /// like `IntensityTable`. None if it's dark everywhere
+    pub fn from_samples(values: &[f32], columns: usize, rows: usize) -> Option<Self> {
+        let row = |j: usize| &values[j * columns..(j + 1) * columns];
+        let cells: Vec<Vec<f32>> = (0..rows - 1)
+            .map(|j| {
+                row(j)
+                    .iter()
+                    .zip(row(j + 1))
+                    .map(|(below, above)| (below + above) / 2.)
+                    .collect()
+            })
+            .collect();
+
+        let masses = cells.iter().map(|cell| {
+            cell.windows(2)
+                .map(|pair| (pair[0] + pair[1]).max(0.) / 2.)
+                .sum::<f32>()
+        });
+        Some(Cdf2d {
+            rows: Cdf::from_cells(masses)?,
+            columns: cells.iter().map(|cell| Cdf::from_samples(cell)).collect(),
+        })
+    }
+
+    /// where (`u`, `v`) (each 0 to 1) lands, as (x, y) each 0 to 1
+    pub fn sample(&self, u: f32, v: f32) -> (f32, f32) {
+        let y = self.rows.sample(v);
+        let row = ((y * self.columns.len() as f32) as usize).min(self.columns.len() - 1);
+        // the row cdf never lands on an empty row
+        let x = self.columns[row]
+            .as_ref()
+            .map_or(0.5, |columns| columns.sample(u));
+        (x, y)
+    }
+}
+
+// how the pattern for one line gets picked from
+#[derive(Debug, Clone)]
+enum Pattern {
+    // x and y independently, like the plain slits and 1d masks
+    Split {
+        x: Option<Cdf>,
+        // the pattern through each slit on its own, for the which path detector.
+        // indexed by slit, only filled in when the detector is on
+        tagged: Vec<Option<Cdf>>,
+        y: Option<Cdf>,
+    },
+    // both at once, for 2d masks
+    Joint(Option<Cdf2d>),
+}
+
+/// one particle on the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub line: usize,      // which of `PatternSampler::lines` it came from
+    pub tag: Option<u32>, // the slit the which path detector caught it at
+    pub x: f32,           // 0 to 1, left to right
+    pub y: f32,           // 0 to 1, bottom to top
+}
+
+/// everything needed to drop particles on the screen for the current setup
+#[derive(Debug, Clone)]
+pub struct PatternSampler {
+    /// the lines the particles come from, see `spectrum`
+    pub lines: Vec<SpectralLine>,
+    patterns: Vec<Pattern>,
+    // the slits the detector can catch a particle at and how bright each one is
+    paths: Vec<(u32, f32)>,
+    which_path: f32,
+}
+
+impl PatternSampler {
+    /// tabulates the pattern for every line of the source.
+    /// not cheap, so only when the setup changes
+    pub fn new(slit: &SlitStructure, far_field: Option<&FarField>) -> Self {
+        let lines = spectrum(slit);
+        let points = |f: &dyn Fn(f32) -> f32| -> Option<Cdf> {
+            let values: Vec<f32> = (0..PROFILE_SAMPLES)
+                .map(|i| f(i as f32 / (PROFILE_SAMPLES - 1) as f32))
+                .collect();
+            Cdf::from_samples(&values)
+        };
+
+        let patterns = lines
+            .iter()
+            .map(|line| {
+                let wavelength = line.wavelength;
+                let table = intensity_table(slit, far_field, wavelength);
+                if table.rows > 1 {
+                    return Pattern::Joint(Cdf2d::from_samples(
+                        &table.values,
+                        table.columns,
+                        table.rows,
+                    ));
+                }
+
+                // masks don't have slits to watch
+                let tagged = if far_field.is_none() && slit.which_path > 0. {
+                    (0..slit.slit_count)
+                        .map(|index| {
+                            if !slit.covered[index as usize] && slit_power(slit, index) > 0. {
+                                points(&|x| tagged_intensity_x(slit, x, wavelength, index))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                };
+
+                // the table has the detector's washing out in it already, for the light.
+                // here that's down to which particles get tagged, so the rest
+                // land as if it was off
+                let x = if far_field.is_none() {
+                    points(&|x| untagged_intensity_x(slit, x, wavelength))
+                } else {
+                    Cdf::from_samples(&table.values)
+                };
+                Pattern::Split {
+                    x,
+                    tagged,
+                    y: points(&|y| intensity_y(slit, y, wavelength)),
+                }
+            })
+            .collect();
+
+        // brighter slits catch more of them
+        let paths = slit
+            .open_slits()
+            .map(|i| (i, slit_power(slit, i)))
+            .filter(|(_, power)| *power > 0.)
+            .collect();
+
+        PatternSampler {
+            lines,
+            patterns,
+            paths,
+            which_path: if far_field.is_none() {
+                slit.which_path
+            } else {
+                0.
+            },
+        }
+    }
+
+    /// one particle, every random choice along the way coming from `rng`,
+    /// so the same seed always gives the same particles. None if nothing gets through
+    pub fn draw(&self, rng: &mut impl Rng) -> Option<Hit> {
+        // each particle comes from one line of the spectrum, picked by brightness
+        let line = pick_line(&self.lines, rng.gen());
+
+        // the detector catches this many of them going through one slit or the other,
+        // and those can't interfere
+        let tag = if rng.gen::<f32>() < self.which_path {
+            self.paths
+                .choose_weighted(rng, |(_, power)| *power)
+                .ok()
+                .map(|(i, _)| *i)
+        } else {
+            None
+        };
+
+        let (x, y) = self.position(line, tag, rng.gen(), rng.gen())?;
+        Some(Hit { line, tag, x, y })
+    }
+
+    /// where a particle from line `line` lands, as (x, y) each 0 to 1, for `tag` set
+    /// if the which path detector caught it going through that slit.
+    /// `u` and `v` are uniform between 0 and 1. None if nothing gets there
+    fn position(&self, line: usize, tag: Option<u32>, u: f32, v: f32) -> Option<(f32, f32)> {
+        match &self.patterns[line] {
+            Pattern::Split { x, tagged, y } => {
+                let x = match tag {
+                    Some(index) => tagged.get(index as usize)?.as_ref()?,
+                    None => x.as_ref()?,
+                };
+                Some((x.sample(u), y.as_ref()?.sample(v)))
+            }
+            Pattern::Joint(joint) => Some(joint.as_ref()?.sample(u, v)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Source, MAX_SLIT_COUNT};
+    use crate::physics::intensity_x;
+    use crate::units::Length;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn flat_pattern_samples_evenly() {
+        let cdf = Cdf::from_samples(&[1.; 11]).unwrap();
+        for u in [0., 0.25, 0.5, 0.9, 1.] {
+            assert!((cdf.sample(u) - u).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn dark_cells_are_never_picked() {
+        // only the middle of three cells is lit
+        let cdf = Cdf::from_cells([0., 2., 0.]).unwrap();
+        for i in 0..=100 {
+            let x = cdf.sample(i as f32 / 100.);
+            assert!((1. / 3. ..=2. / 3.).contains(&x));
+        }
+    }
+
+    #[test]
+    fn dark_screen_gives_nothing() {
+        assert!(Cdf::from_samples(&[0.; 16]).is_none());
+        assert!(Cdf2d::from_samples(&[0.; 16], 4, 4).is_none());
+
+        let slit = SlitStructure {
+            transmissions: [0.; MAX_SLIT_COUNT as usize],
+            ..SlitStructure::default()
+        };
+        let sampler = PatternSampler::new(&slit, None);
+        assert!(sampler.position(0, None, 0.5, 0.5).is_none());
+    }
+
+    #[test]
+    fn joint_pattern_finds_the_bright_corner() {
+        // 3 x 3 samples, only the top right one lit
+        let mut values = [0.; 9];
+        values[8] = 1.;
+        let cdf = Cdf2d::from_samples(&values, 3, 3).unwrap();
+        for (u, v) in [(0., 0.), (0.5, 0.5), (1., 1.), (0.1, 0.9)] {
+            let (x, y) = cdf.sample(u, v);
+            assert!(x >= 0.5 && y >= 0.5);
+        }
+    }
+
+    #[test]
+    fn seed_42_still_gives_the_same_particles() {
+        // pinned, so a change to the draw order or the rng shows up here.
+        // if it's on purpose, the seeds people saved won't give their runs back any more.
+        // recorded from the revision that added seeding, with rand 0.8's StdRng
+        let sampler = PatternSampler::new(&SlitStructure::default(), None);
+        let mut rng = StdRng::seed_from_u64(42);
+        let golden = [
+            (0.43203655, 0.51935136),
+            (0.88036954, 0.45710614),
+            (0.52677023, 0.46129173),
+            (0.41925964, 0.68162656),
+        ];
+        for (x, y) in golden {
+            let hit = sampler.draw(&mut rng).unwrap();
+            assert_eq!((hit.line, hit.tag), (0, None));
+            assert!((hit.x - x).abs() < 1e-5 && (hit.y - y).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn detector_washes_out_the_fringes_like_the_light() {
+        // half of them caught at a slit. the light's fringes keep half their visibility,
+        // the particles' have to as well, not a quarter
+        let slit = SlitStructure {
+            which_path: 0.5,
+            ..SlitStructure::default()
+        };
+        let sampler = PatternSampler::new(&slit, None);
+        let wavelength = sampler.lines[0].wavelength;
+
+        // a fringe is 10 bins wide, and the middle bin sits right on the bright one
+        let bins = 401;
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut counts = vec![0.; bins];
+        for _ in 0..400000 {
+            let x = sampler.draw(&mut rng).unwrap().x;
+            counts[((x * bins as f32) as usize).min(bins - 1)] += 1.;
+        }
+        let light: Vec<f32> = (0..bins)
+            .map(|i| {
+                (0..16)
+                    .map(|k| {
+                        let x = (i as f32 + (k as f32 + 0.5) / 16.) / bins as f32;
+                        intensity_x(&slit, x, wavelength)
+                    })
+                    .sum::<f32>()
+            })
+            .collect();
+
+        // from the middle of the bright fringe out past the dark ones either side
+        let visibility = |values: &[f32]| {
+            let middle = &values[bins / 2 - 8..=bins / 2 + 8];
+            let most = middle.iter().fold(0_f32, |most, value| most.max(*value));
+            let least = middle
+                .iter()
+                .fold(f32::MAX, |least, value| least.min(*value));
+            (most - least) / (most + least)
+        };
+        assert!((visibility(&light) - 0.5).abs() < 0.05);
+        assert!((visibility(&counts) - visibility(&light)).abs() < 0.05);
+    }
+
+    #[test]
+    fn same_seed_same_particles() {
+        let slit = SlitStructure {
+            source: Source::Sodium,
+            which_path: 0.5,
+            ..SlitStructure::default()
+        };
+        let sampler = PatternSampler::new(&slit, None);
+        let run = |seed: u64| -> Vec<Hit> {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..200).map(|_| sampler.draw(&mut rng).unwrap()).collect()
+        };
+
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+    }
+
+    #[test]
+    fn particles_follow_the_fringes() {
+        // a narrow pattern, lots of fringes, the kind rejection sampling crawled through
+        let slit = SlitStructure {
+            separation: Length::micrometers(100.),
+            ..SlitStructure::default()
+        };
+        let sampler = PatternSampler::new(&slit, None);
+        let wavelength = sampler.lines[0].wavelength;
+
+        // evenly spread u's, so the histogram should follow the intensity closely
+        let draws = 20000;
+        let bins = PROFILE_SAMPLES / 4;
+        let mut counts = vec![0.; bins];
+        for i in 0..draws {
+            let u = (i as f32 + 0.5) / draws as f32;
+            let (x, _) = sampler.position(0, None, u, 0.5).unwrap();
+            counts[((x * bins as f32) as usize).min(bins - 1)] += 1.;
+        }
+
+        // averaged over each bin, a fringe is only a couple of bins wide
+        let expected: Vec<f32> = (0..bins)
+            .map(|i| {
+                (0..16)
+                    .map(|k| {
+                        let x = (i as f32 + (k as f32 + 0.5) / 16.) / bins as f32;
+                        intensity_x(&slit, x, wavelength)
+                    })
+                    .sum::<f32>()
+            })
+            .collect();
+        let total: f32 = expected.iter().sum();
+        for (count, expected) in counts.iter().zip(&expected) {
+            let expected = expected / total * draws as f32;
+            assert!((count - expected).abs() < 0.05 * draws as f32 / bins as f32 + 0.2 * expected);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// what the source actually puts out. everything downstream works one wavelength
+// at a time, so a spectrum is just a list of weighted lines and continuous
+// spectra get chopped up into narrow bands
+
+use crate::component::{SlitStructure, Source};
+use crate::units::Wavelength;
+
+use super::matter::matter_wavelength;
+
+// edges of what we can see
+pub const VISIBLE_MIN: Wavelength = Wavelength::nanometers(380.);
+pub const VISIBLE_MAX: Wavelength = Wavelength::nanometers(780.);
+
+// width of each band a continuous spectrum gets chopped into
+pub const CONTINUUM_STEP: Wavelength = Wavelength::nanometers(10.);
+
+// color temperature of an old fashioned light bulb, kelvin
+pub const INCANDESCENT_TEMPERATURE: f32 = 2700.;
+
+// the sodium d lines, d2 is about twice as bright as d1
+const SODIUM_LINES: [(Wavelength, f32); 2] = [
+    (Wavelength::nanometers(588.995), 2.),
+    (Wavelength::nanometers(589.592), 1.),
+];
+
+// the bright visible lines of a low pressure mercury lamp, strengths are rough
+const MERCURY_LINES: [(Wavelength, f32); 5] = [
+    (Wavelength::nanometers(404.656), 1.8),
+    (Wavelength::nanometers(435.833), 4.),
+    (Wavelength::nanometers(546.074), 5.),
+    (Wavelength::nanometers(576.960), 1.),
+    (Wavelength::nanometers(579.066), 1.1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralLine {
+    pub wavelength: Wavelength,
+    pub weight: f32, // the weights of a spectrum add up to 1
+}
+
+/// the lines making up the light from `slit.source`. a laser is the one
+/// line at `slit.wavelength`, the presets ignore it. matter waves are one line too,
+/// at their de broglie wavelength
+pub fn spectrum(slit: &SlitStructure) -> Vec<SpectralLine> {
+    let lines: Vec<(Wavelength, f32)> = match slit.source {
+        Source::Laser => vec![(slit.wavelength, 1.)],
+        Source::Sodium => SODIUM_LINES.to_vec(),
+        Source::Mercury => MERCURY_LINES.to_vec(),
+        Source::White => continuum(|_| 1.),
+        Source::Incandescent => {
+            continuum(|wavelength| blackbody(wavelength, INCANDESCENT_TEMPERATURE))
+        }
+        Source::Electron | Source::Neutron | Source::Helium | Source::C60 => {
+            matter_wavelength(slit)
+                .map(|wavelength| vec![(wavelength, 1.)])
+                .unwrap_or_default()
+        }
+    };
+
+    let total: f32 = lines.iter().map(|(_, weight)| weight).sum();
+    lines
+        .into_iter()
+        .map(|(wavelength, weight)| SpectralLine {
+            wavelength,
+            weight: weight / total,
+        })
+        .collect()
+}
+
+/// the index of the line a number `u` between 0 and 1 lands on, with each line
+/// getting a share of 0 to 1 matching its weight
+pub fn pick_line(lines: &[SpectralLine], u: f32) -> usize {
+    let mut remaining = u;
+    for (i, line) in lines.iter().enumerate() {
+        if remaining < line.weight {
+            return i;
+        }
+        remaining -= line.weight;
+    }
+    // only rounding gets us here
+    lines.len() - 1
+}
+
+// the middle of each band across the visible range, weighted by `power`
+fn continuum(power: impl Fn(Wavelength) -> f32) -> Vec<(Wavelength, f32)> {
+    let bands = ((VISIBLE_MAX - VISIBLE_MIN) / CONTINUUM_STEP) as usize;
+    (0..bands)
+        .map(|i| {
+            let wavelength = VISIBLE_MIN + CONTINUUM_STEP * (i as f32 + 0.5);
+            (wavelength, power(wavelength))
+        })
+        .collect()
+}
+
+/// planck's law, up to a constant
+fn blackbody(wavelength: Wavelength, temperature: f32) -> f32 {
+    // second radiation constant hc / k, in micrometer kelvin
+    const C2: f32 = 14387.8;
+    let micrometers = wavelength.in_micrometers();
+    1. / (micrometers.powi(5) * ((C2 / (micrometers * temperature)).exp() - 1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_add_up_to_one() {
+        for source in [
+            Source::Laser,
+            Source::Sodium,
+            Source::Mercury,
+            Source::White,
+            Source::Incandescent,
+        ] {
+            let slit = SlitStructure {
+                source,
+                ..SlitStructure::default()
+            };
+            let total: f32 = spectrum(&slit).iter().map(|line| line.weight).sum();
+            assert!((total - 1.).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn light_bulbs_are_red() {
+        let slit = SlitStructure {
+            source: Source::Incandescent,
+            ..SlitStructure::default()
+        };
+        let lines = spectrum(&slit);
+        assert!(lines[0].weight < lines[lines.len() - 1].weight);
+    }
+
+    #[test]
+    fn picks_follow_the_weights() {
+        let slit = SlitStructure {
+            source: Source::Sodium,
+            ..SlitStructure::default()
+        };
+        let lines = spectrum(&slit);
+        assert_eq!(lines[pick_line(&lines, 0.)].wavelength, SODIUM_LINES[0].0);
+        assert_eq!(lines[pick_line(&lines, 0.6)].wavelength, SODIUM_LINES[0].0);
+        assert_eq!(lines[pick_line(&lines, 0.7)].wavelength, SODIUM_LINES[1].0);
+        assert_eq!(lines[pick_line(&lines, 1.)].wavelength, SODIUM_LINES[1].0);
+    }
+}
@@ -0,0 +1,174 @@
+// a 2d scalar wave stepped forward in time, for watching the wavefronts
+// actually get through the slits and spread out. plain leapfrog finite differences
+// on a grid of square cells, with the source along the bottom, the slit barrier
+// just above it, and sponge layers round the edges soaking up whatever hits them
+
+use std::f32::consts::PI;
+
+// how far a wave gets in one step, in cells. has to stay under 1 / sqrt(2) or it blows up
+pub const COURANT: f32 = 0.5;
+
+// how thick the absorbing layer round the edges is, in cells
+pub const SPONGE: usize = 12;
+// damping per step at the very edge, it ramps up from nothing over the sponge
+const SPONGE_DAMPING: f32 = 0.25;
+
+// rows from the bottom, the source is inside the bottom sponge's reach
+// so the half of the wave heading down gets soaked up
+pub const SOURCE_ROW: usize = SPONGE + 2;
+pub const BARRIER_ROW: usize = SOURCE_ROW + 8;
+pub const BARRIER_THICKNESS: usize = 2;
+
+pub struct WaveField {
+    pub columns: usize,
+    pub rows: usize,
+    current: Vec<f32>,
+    previous: Vec<f32>,
+    wall: Vec<bool>,
+    damping: Vec<f32>,
+    // angular frequency per step and weight, one per line of the spectrum
+    lines: Vec<(f32, f32)>,
+    steps: u32,
+}
+
+impl WaveField {
+    /// a still grid with a barrier that's solid apart from `openings`, given as
+    /// (center, width) in cells from the middle. `lines` are (wavelength in cells, weight)
+    pub fn new(columns: usize, rows: usize, openings: &[(f32, f32)], lines: &[(f32, f32)]) -> Self {
+        let mut wall = vec![false; columns * rows];
+        for j in BARRIER_ROW..BARRIER_ROW + BARRIER_THICKNESS {
+            for i in 0..columns {
+                let x = i as f32 + 0.5 - columns as f32 / 2.;
+                wall[j * columns + i] = !openings
+                    .iter()
+                    .any(|(center, width)| (x - center).abs() < width / 2.);
+            }
+        }
+
+        let mut damping = vec![0.; columns * rows];
+        for j in 0..rows {
+            for i in 0..columns {
+                let edge = i.min(columns - 1 - i).min(j).min(rows - 1 - j);
+                if edge < SPONGE {
+                    let depth = (SPONGE - edge) as f32 / SPONGE as f32;
+                    damping[j * columns + i] = SPONGE_DAMPING * depth * depth;
+                }
+            }
+        }
+
+        WaveField {
+            columns,
+            rows,
+            current: vec![0.; columns * rows],
+            previous: vec![0.; columns * rows],
+            wall,
+            damping,
+            lines: lines
+                .iter()
+                .map(|(wavelength, weight)| (2. * PI * COURANT / wavelength, *weight))
+                .collect(),
+            steps: 0,
+        }
+    }
+
+    /// one step of u'' = c^2 (u_xx + u_yy)
+    pub fn step(&mut self) {
+        let columns = self.columns;
+        let c2 = COURANT * COURANT;
+        let mut next = vec![0.; self.current.len()];
+
+        for j in 1..self.rows - 1 {
+            for i in 1..columns - 1 {
+                let k = j * columns + i;
+                if self.wall[k] {
+                    continue;
+                }
+
+                let u = self.current[k];
+                let laplacian = self.current[k - 1]
+                    + self.current[k + 1]
+                    + self.current[k - columns]
+                    + self.current[k + columns]
+                    - 4. * u;
+                let damping = self.damping[k];
+
+                next[k] =
+                    (2. * u - (1. - damping) * self.previous[k] + c2 * laplacian) / (1. + damping);
+            }
+        }
+
+        // soft source, adds to whatever's already passing through. a whole row kicking
+        // by 2 COURANT omega cos(omega t) each step makes a plane wave of amplitude 1,
+        // and cos rather than sin so it doesn't drift off zero
+        self.steps += 1;
+        let t = self.steps as f32;
+        let drive: f32 = self
+            .lines
+            .iter()
+            .map(|(omega, weight)| weight * (omega * t).cos() * omega * 2. * COURANT)
+            .sum();
+        for i in 0..columns {
+            next[SOURCE_ROW * columns + i] += drive;
+        }
+
+        self.previous = std::mem::replace(&mut self.current, next);
+    }
+
+    /// the field at cell (`i`, `j`), counting up from the bottom left.
+    /// a plane wave from the source swings about -1 to 1
+    pub fn value(&self, i: usize, j: usize) -> f32 {
+        self.current[j * self.columns + i]
+    }
+
+    pub fn is_wall(&self, i: usize, j: usize) -> bool {
+        self.wall[j * self.columns + i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: usize = 120;
+    const ROWS: usize = 80;
+
+    fn run(field: &mut WaveField, steps: usize) {
+        for _ in 0..steps {
+            field.step();
+        }
+    }
+
+    fn peak_above_barrier(field: &WaveField) -> f32 {
+        let mut peak: f32 = 0.;
+        for j in BARRIER_ROW + BARRIER_THICKNESS + 5..field.rows - SPONGE {
+            for i in SPONGE..field.columns - SPONGE {
+                peak = peak.max(field.value(i, j).abs());
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn solid_barrier_blocks_everything() {
+        let mut field = WaveField::new(COLUMNS, ROWS, &[], &[(10., 1.)]);
+        run(&mut field, 400);
+        assert!(peak_above_barrier(&field) < 1e-6);
+    }
+
+    #[test]
+    fn plane_wave_has_unit_amplitude() {
+        // no barrier to speak of, so it's just the source
+        let mut field = WaveField::new(COLUMNS, ROWS, &[(0., COLUMNS as f32)], &[(10., 1.)]);
+        run(&mut field, 400);
+        let peak = peak_above_barrier(&field);
+        assert!(peak > 0.7 && peak < 1.3, "peak {peak}");
+    }
+
+    #[test]
+    fn slit_lets_some_through() {
+        let mut field = WaveField::new(COLUMNS, ROWS, &[(0., 6.)], &[(10., 1.)]);
+        run(&mut field, 400);
+        let peak = peak_above_barrier(&field);
+        assert!(peak > 0.05 && peak < 1., "peak {peak}");
+    }
+}
@@ -1,23 +1,54 @@
 use crate::{
-    component::{DisplayInfo, Increment, Light, Slit, SlitControl, SlitScreen, SlitStructure},
+    component::{
+        Aperture, CurrentPage, DataExport, Diffraction, DisplayInfo, Increment, Light,
+        ParticleDetector, ParticleEmitter, Plate, PlateMarker, Slit, SlitControl, SlitScreen,
+        SlitStructure,
+    },
     interference::{BASELINE_Y_SLITS, SLIT_SCREEN_HEIGHT},
-    ui::{setup_ui, BACKDROUND_COLOR, NORMAL_BUTTON, PRESSED_BUTTON, SLIT_COLOR},
+    physics::{
+        is_near_field,
+        matter::{display_wavelength, slit_unit, species},
+        spectrum::{spectrum, SpectralLine, VISIBLE_MAX, VISIBLE_MIN},
+        width_of,
+    },
+    ui::{
+        change_page_system, detector_button_system, emitter_button_system, export_button_system,
+        on_off, polarizer_angle, setup_ui, update_detector_display, update_emitter_display,
+        update_export_display, update_pages, BACKDROUND_COLOR, COVERED_SLIT_COLOR, NORMAL_BUTTON,
+        PLATE_COLOR, PRESSED_BUTTON, SLIT_COLOR,
+    },
+    units::{Length, Wavelength},
 };
 use bevy::math::{f32::Quat, vec4};
+use bevy::window::PrimaryWindow;
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
 pub struct SlitPlugin;
 impl Plugin for SlitPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_ui)
+        app.init_resource::<CurrentPage>()
+            .add_startup_system(setup_ui)
             .add_startup_system(setup_slits)
             .add_system(increment_sep_system)
+            .add_system(change_page_system)
+            .add_system(update_pages.run_if(resource_changed::<CurrentPage>()))
+            .add_system(emitter_button_system)
+            .add_system(update_emitter_display.run_if(resource_changed::<ParticleEmitter>()))
+            .add_system(detector_button_system)
+            .add_system(update_detector_display.run_if(resource_changed::<ParticleDetector>()))
+            .add_system(export_button_system)
+            .add_system(update_export_display.run_if(resource_changed::<DataExport>()))
+            .add_system(cover_slit_system)
             .add_systems(
                 (
+                    update_slit_count,
+                    update_slit_covered,
                     update_slit_distance,
                     update_slit_width,
                     update_slit_separation,
+                    update_plate,
                     interpolate_light_color,
+                    update_laser_width,
                     update_display_buttons,
                 )
                     .distributive_run_if(update_display_criteria),
@@ -29,6 +60,17 @@ const BASELINE_SLIT_WIDTH: f32 = 1.5;
 const BASELINE_SLIT_HEIGHT: f32 = 5.;
 const BASELINE_SLIT_SCREEN_WIDTH: f32 = 300.;
 const BASELINE_SLIT_SCREEN_X: f32 = -200.;
+// how far apart the slits are drawn at the default separation
+const BASELINE_SLIT_SPACING: f32 = BASELINE_SLIT_WIDTH * 6.;
+// leave a bit of the slit screen on either side of a wide grating
+const MAX_GRATING_WIDTH: f32 = BASELINE_SLIT_SCREEN_WIDTH - 20.;
+const SLIT_CLICK_RADIUS: f32 = 4.;
+// the plate covers the half of the slit the light comes in from
+const PLATE_WIDTH: f32 = 5.;
+const PLATE_HEIGHT: f32 = BASELINE_SLIT_HEIGHT * BASELINE_SLIT_HEIGHT / 2.;
+// how much wider the laser gets per micrometer of source per centimeter away,
+// i.e. by how big the source looks from the slits
+const LASER_WIDTH_PER_ANGLE: f32 = 1. / 20.;
 
 fn setup_slits(
     mut commands: Commands,
@@ -46,35 +88,7 @@ fn setup_slits(
         })
         .insert(SlitScreen)
         .with_children(|parent| {
-            parent
-                .spawn(MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(shape::Box::new(BASELINE_SLIT_WIDTH, BASELINE_SLIT_HEIGHT, 0.).into())
-                        .into(),
-                    material: materials.add(ColorMaterial::from(BACKDROUND_COLOR)),
-                    transform: Transform::from_translation(Vec3::new(
-                        BASELINE_SLIT_WIDTH * 3.,
-                        0.,
-                        0.1,
-                    )),
-                    ..default()
-                })
-                .insert(Slit::RightSlit);
-
-            parent
-                .spawn(MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(shape::Box::new(BASELINE_SLIT_WIDTH, BASELINE_SLIT_HEIGHT, 0.).into())
-                        .into(),
-                    material: materials.add(ColorMaterial::from(BACKDROUND_COLOR)),
-                    transform: Transform::from_translation(Vec3::new(
-                        -BASELINE_SLIT_WIDTH * 3.,
-                        0.,
-                        0.1,
-                    )),
-                    ..default()
-                })
-                .insert(Slit::LeftSlit);
+            // the slits themselves get spawned by update_slit_count
 
             let laser_height = SLIT_SCREEN_HEIGHT - BASELINE_SLIT_HEIGHT;
             parent
@@ -84,7 +98,7 @@ fn setup_slits(
                         .into(),
                     material: materials.add(ColorMaterial::from(Color::BLUE)),
                     transform: Transform::from_translation(Vec3::new(
-                        -0.5 * laser_height * (15. as f32).sin(),
+                        -0.5 * laser_height * 15_f32.sin(),
                         -(laser_height / 2.) + BASELINE_SLIT_HEIGHT * 2.,
                         0.2,
                     ))
@@ -96,11 +110,7 @@ fn setup_slits(
 }
 
 pub fn update_display_criteria(slit_structure: Res<SlitStructure>) -> bool {
-    if slit_structure.is_changed() {
-        true
-    } else {
-        false
-    }
+    slit_structure.is_changed()
 }
 
 pub fn interpolate_light_color(
@@ -109,24 +119,37 @@ pub fn interpolate_light_color(
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     for handle in display_query.iter() {
-        let mut color_mat = materials.get_mut(&handle).unwrap();
-
-        let wavelength = slit_structure.wavelength;
+        let color_mat = materials.get_mut(handle).unwrap();
 
-        let min_visible = 380.;
-        let max_visible: f32 = 780.;
+        let lines = spectrum(&slit_structure);
 
-        if wavelength <= min_visible || wavelength >= max_visible {
+        if lines.iter().all(|line| {
+            let wavelength = display_wavelength(&slit_structure, line.wavelength);
+            wavelength <= VISIBLE_MIN || wavelength >= VISIBLE_MAX
+        }) {
             // we cannot actually see this light
             color_mat.color = Color::BLACK;
             return;
         } else {
-            color_mat.color = wavelength_to_rgb(&wavelength);
+            color_mat.color = spectrum_to_rgb(&slit_structure, &lines);
         }
     }
 }
 
-pub fn wavelength_to_rgb(lambda: &f32) -> Color {
+// what a whole spectrum looks like, each line's color mixed in by its weight
+pub fn spectrum_to_rgb(slit_structure: &SlitStructure, lines: &[SpectralLine]) -> Color {
+    let (red, green, blue) = lines.iter().fold((0., 0., 0.), |(r, g, b), line| {
+        let color = wavelength_to_rgb(&display_wavelength(slit_structure, line.wavelength));
+        (
+            r + color.r() * line.weight,
+            g + color.g() * line.weight,
+            b + color.b() * line.weight,
+        )
+    });
+    Color::rgb(red, green, blue)
+}
+
+pub fn wavelength_to_rgb(lambda: &Wavelength) -> Color {
     // turns out there is not an Authoritative Relationship
     // between color and wavelength (unlike, say, sound and wavelength/freq.)
     // Bonkers! you gotta just swing ur own
@@ -136,32 +159,32 @@ pub fn wavelength_to_rgb(lambda: &f32) -> Color {
     let mut blue;
     let mut green;
     let factor;
-    let wavelength = lambda.clone();
+    let wavelength = lambda.in_nanometers();
 
     const INTENSITYMAX: f32 = 1.;
     const GAMMA: f32 = 0.8;
 
-    if (wavelength >= 380.) && (wavelength < 440.) {
+    if (380. ..440.).contains(&wavelength) {
         red = -(wavelength - 440.) / (440. - 380.);
         green = 0.0;
         blue = 1.0;
-    } else if (wavelength >= 440.) && (wavelength < 490.) {
+    } else if (440. ..490.).contains(&wavelength) {
         red = 0.0;
         green = (wavelength - 440.) / (490. - 440.);
         blue = 1.0;
-    } else if (wavelength >= 490.) && (wavelength < 510.) {
+    } else if (490. ..510.).contains(&wavelength) {
         red = 0.0;
         green = 1.0;
         blue = -(wavelength - 510.) / (510. - 490.);
-    } else if (wavelength >= 510.) && (wavelength < 580.) {
+    } else if (510. ..580.).contains(&wavelength) {
         red = (wavelength - 510.) / (580. - 510.);
         green = 1.0;
         blue = 0.0;
-    } else if (wavelength >= 580.) && (wavelength < 645.) {
+    } else if (580. ..645.).contains(&wavelength) {
         red = 1.0;
         green = -(wavelength - 645.) / (645. - 580.);
         blue = 0.0;
-    } else if (wavelength >= 645.) && (wavelength < 781.) {
+    } else if (645. ..781.).contains(&wavelength) {
         red = 1.0;
         green = 0.0;
         blue = 0.0;
@@ -171,11 +194,11 @@ pub fn wavelength_to_rgb(lambda: &f32) -> Color {
         blue = 0.0;
     };
     // Let the intensity fall off near the vision limits
-    if (wavelength >= 380.) && (wavelength < 420.) {
+    if (380. ..420.).contains(&wavelength) {
         factor = 0.3 + 0.7 * (wavelength - 380.) / (420. - 380.);
-    } else if (wavelength >= 420.) && (wavelength < 701.) {
+    } else if (420. ..701.).contains(&wavelength) {
         factor = 1.0;
-    } else if (wavelength >= 701.) && (wavelength < 781.) {
+    } else if (701. ..781.).contains(&wavelength) {
         factor = 0.3 + 0.7 * (780. - wavelength) / (780. - 700.);
     } else {
         factor = 0.0;
@@ -191,7 +214,7 @@ pub fn wavelength_to_rgb(lambda: &f32) -> Color {
         blue = INTENSITYMAX * f32::powf(blue * factor, GAMMA);
     }
 
-    return Color::rgb(red, green, blue);
+    Color::rgb(red, green, blue)
 }
 
 pub fn _lerp_hsv(t: f32) -> Color {
@@ -202,24 +225,166 @@ pub fn _lerp_hsv(t: f32) -> Color {
 
     let h = b.x - t * d;
 
-    return Color::Hsla {
+    Color::Hsla {
         hue: h,
         saturation: 1.,
         lightness: 0.5,
         alpha: 1.,
+    }
+}
+
+pub fn slit_scale(index: u32, slit_structure: &SlitStructure) -> Vec3 {
+    // in the units on the buttons, 5 is the default either way
+    let slit_width = width_of(slit_structure, index) / slit_unit(slit_structure);
+    Vec3::new(
+        // narrow ones would go negative otherwise
+        (BASELINE_SLIT_WIDTH - (5. - slit_width) / 5.).max(0.2),
+        BASELINE_SLIT_HEIGHT,
+        0.1,
+    )
+}
+
+pub fn slit_translation(index: u32, slit_structure: &SlitStructure) -> Vec3 {
+    let count = slit_structure.slit_count as f32;
+    // squish the spacing down if a big grating wouldn't fit on the slit screen
+    let separation = slit_structure.separation / slit_unit(slit_structure);
+    let spacing =
+        (BASELINE_SLIT_SPACING * separation / 50.).min(MAX_GRATING_WIDTH / (count - 1.).max(1.));
+
+    Vec3::new((index as f32 - (count - 1.) / 2.) * spacing, 0., 0.1)
+}
+
+pub fn update_slit_count(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    screen_query: Query<Entity, With<SlitScreen>>,
+    slit_query: Query<Entity, With<Slit>>,
+    slit_structure: Res<SlitStructure>,
+) {
+    if slit_query.iter().count() == slit_structure.slit_count as usize {
+        return;
+    }
+
+    for entity in slit_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let mesh = meshes.add(shape::Box::new(BASELINE_SLIT_WIDTH, BASELINE_SLIT_HEIGHT, 0.).into());
+
+    for screen in screen_query.iter() {
+        commands.entity(screen).with_children(|parent| {
+            for i in 0..slit_structure.slit_count {
+                parent
+                    .spawn(MaterialMesh2dBundle {
+                        mesh: mesh.clone().into(),
+                        // every slit gets its own material so it can be covered on its own
+                        material: materials
+                            .add(ColorMaterial::from(slit_color(i, &slit_structure))),
+                        transform: Transform::from_translation(slit_translation(
+                            i,
+                            &slit_structure,
+                        ))
+                        .with_scale(slit_scale(i, &slit_structure)),
+                        ..default()
+                    })
+                    .insert(Slit(i));
+            }
+        });
+    }
+}
+
+// half covered slits are somewhere in between
+fn slit_color(index: u32, slit_structure: &SlitStructure) -> Color {
+    if slit_structure.covered[index as usize] {
+        COVERED_SLIT_COLOR
+    } else {
+        let transmission = slit_structure.transmissions[index as usize];
+        let covered = COVERED_SLIT_COLOR.as_rgba_f32();
+        let open = BACKDROUND_COLOR.as_rgba_f32();
+        Color::rgba(
+            covered[0] + (open[0] - covered[0]) * transmission,
+            covered[1] + (open[1] - covered[1]) * transmission,
+            covered[2] + (open[2] - covered[2]) * transmission,
+            1.,
+        )
+    }
+}
+
+pub fn update_slit_covered(
+    slit_query: Query<(&Handle<ColorMaterial>, &Slit)>,
+    slit_structure: Res<SlitStructure>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (handle, slit) in slit_query.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = slit_color(slit.0, &slit_structure);
+        }
+    }
+}
+
+pub fn cover_slit_system(
+    mouse: Res<Input<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    slit_query: Query<(&GlobalTransform, &Slit)>,
+    mut slit_structure: ResMut<SlitStructure>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
     };
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(cursor) = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    // slits are only a pixel or two wide, so be generous about what counts as a click on one
+    let clicked = slit_query
+        .iter()
+        .map(|(transform, slit)| (transform.translation().truncate() - cursor, slit.0))
+        .filter(|(offset, _)| {
+            offset.x.abs() < SLIT_CLICK_RADIUS
+                && offset.y.abs() < BASELINE_SLIT_HEIGHT * BASELINE_SLIT_HEIGHT / 2.
+        })
+        .min_by(|(a, _), (b, _)| a.x.abs().total_cmp(&b.x.abs()));
+
+    if let Some((_, slit)) = clicked {
+        slit_structure.toggle_covered(slit);
+    }
 }
 
 pub fn update_slit_width(
-    mut display_query: Query<&mut Transform, With<Slit>>,
+    mut display_query: Query<(&mut Transform, &Slit)>,
+    slit_structure: Res<SlitStructure>,
+) {
+    for (mut transform, slit) in display_query.iter_mut() {
+        transform.scale = slit_scale(slit.0, &slit_structure);
+    }
+}
+
+// a wide (or close) source gets drawn as a fatter beam
+pub fn update_laser_width(
+    mut display_query: Query<&mut Transform, With<Light>>,
     slit_structure: Res<SlitStructure>,
 ) {
     for mut transform in display_query.iter_mut() {
-        transform.scale = Vec3::new(
-            BASELINE_SLIT_WIDTH - (5. - slit_structure.slit_width) / 5.,
-            BASELINE_SLIT_HEIGHT,
-            0.1,
-        );
+        // micrometers per centimeter
+        let angle = slit_structure.source_width.in_micrometers()
+            / slit_structure.source_distance.in_centimeters();
+        transform.scale.x = 1. + angle * LASER_WIDTH_PER_ANGLE;
     }
 }
 
@@ -229,8 +394,8 @@ pub fn update_slit_distance(
 ) {
     for mut transform in display_query.iter_mut() {
         transform.translation = Vec3::new(
-            BASELINE_SLIT_SCREEN_X + (100. - slit_structure.screen_distance) / 5.,
-            BASELINE_Y_SLITS + (100. - slit_structure.screen_distance) / 5.,
+            BASELINE_SLIT_SCREEN_X + (100. - slit_structure.screen_distance.in_centimeters()) / 5.,
+            BASELINE_Y_SLITS + (100. - slit_structure.screen_distance.in_centimeters()) / 5.,
             0.,
         );
     }
@@ -241,22 +406,49 @@ pub fn update_slit_separation(
     slit_structure: Res<SlitStructure>,
 ) {
     for (mut transform, slit) in display_query.iter_mut() {
-        match slit {
-            Slit::LeftSlit => {
-                transform.translation = Vec3::new(
-                    -(slit_structure.separation - 50.) / 2. - BASELINE_SLIT_WIDTH * 3.,
-                    0.,
-                    0.1,
-                )
-            }
-            Slit::RightSlit => {
-                transform.translation = Vec3::new(
-                    (slit_structure.separation - 50.) / 2. + BASELINE_SLIT_WIDTH * 3.,
-                    0.,
-                    0.1,
-                )
-            }
-        }
+        transform.translation = slit_translation(slit.0, &slit_structure);
+    }
+}
+
+// redrawn whenever anything changes, it's only ever the one
+pub fn update_plate(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    screen_query: Query<Entity, With<SlitScreen>>,
+    plate_query: Query<Entity, With<PlateMarker>>,
+    slit_structure: Res<SlitStructure>,
+) {
+    for entity in plate_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // masks don't know about the plate, see physics::plate_phase
+    if matches!(slit_structure.plate, Plate::None)
+        || !matches!(slit_structure.aperture, Aperture::Slits)
+    {
+        return;
+    }
+
+    let slit = slit_translation(slit_structure.plate_slit, &slit_structure);
+    for screen in screen_query.iter() {
+        commands.entity(screen).with_children(|parent| {
+            parent
+                .spawn(MaterialMesh2dBundle {
+                    mesh: meshes
+                        .add(shape::Box::new(PLATE_WIDTH, PLATE_HEIGHT, 0.).into())
+                        .into(),
+                    material: materials.add(ColorMaterial::from(PLATE_COLOR)),
+                    // just in front of the slit, light comes up from below
+                    transform: Transform::from_translation(Vec3::new(
+                        slit.x,
+                        slit.y - PLATE_HEIGHT / 2.,
+                        0.15,
+                    )),
+                    ..default()
+                })
+                .insert(PlateMarker);
+        });
     }
 }
 
@@ -266,15 +458,70 @@ pub fn update_display_buttons(
 ) {
     for (mut text, slit_type) in display_query.iter_mut() {
         text.sections[0].value = match slit_type {
-            SlitControl::Separation => slit_structure.separation.to_string(),
-            SlitControl::ScreenDistance => (slit_structure.screen_distance / 100.).to_string(),
-            SlitControl::Wavelength => slit_structure.wavelength.to_string(),
-            SlitControl::Width => slit_structure.slit_width.to_string(),
+            SlitControl::Aperture => slit_structure.aperture.to_string(),
+            SlitControl::Separation => slit_length(slit_structure.separation, &slit_structure),
+            SlitControl::SlitCount => slit_structure.slit_count.to_string(),
+            SlitControl::SelectedSlit => (slit_structure.selected_slit + 1).to_string(),
+            SlitControl::SelectedWidth => format!(
+                "{:.1}",
+                slit_structure.width_scales[slit_structure.selected_slit as usize]
+            ),
+            SlitControl::SelectedTransmission => format!(
+                "{:.1}",
+                slit_structure.transmissions[slit_structure.selected_slit as usize]
+            ),
+            SlitControl::SelectedPolarizer => {
+                polarizer_angle(slit_structure.polarizers[slit_structure.selected_slit as usize])
+            }
+            SlitControl::Polarization => slit_structure.polarization.to_string(),
+            SlitControl::Analyzer => polarizer_angle(slit_structure.analyzer),
+            SlitControl::Eraser => on_off(slit_structure.is_eraser()).to_string(),
+            SlitControl::Plate => slit_structure.plate.to_string(),
+            SlitControl::PlateSlit => (slit_structure.plate_slit + 1).to_string(),
+            SlitControl::PlatePhase => slit_structure.plate_phase.to_string(),
+            SlitControl::PlateThickness => {
+                format!("{:.1}", slit_structure.plate_thickness.in_micrometers())
+            }
+            SlitControl::PlateIndex => format!("{:.2}", slit_structure.plate_index),
+            SlitControl::Coherence => slit_structure.coherence.to_string(),
+            SlitControl::WhichPath => format!("{:.1}", slit_structure.which_path),
+            SlitControl::TagSlits => on_off(slit_structure.tag_slits).to_string(),
+            SlitControl::Seed => slit_structure.seed.to_string(),
+            SlitControl::ScreenDistance => slit_structure.screen_distance.meters().to_string(),
+            SlitControl::Source => slit_structure.source.to_string(),
+            SlitControl::Wavelength => slit_structure.wavelength.in_nanometers().to_string(),
+            SlitControl::Voltage => slit_structure.voltage.to_string(),
+            SlitControl::Velocity => slit_structure.velocity.to_string(),
+            SlitControl::SourceWidth => slit_structure.source_width.in_micrometers().to_string(),
+            SlitControl::SourceDistance => {
+                slit_structure.source_distance.in_centimeters().to_string()
+            }
+            SlitControl::Width => slit_length(slit_structure.slit_width, &slit_structure),
+            SlitControl::Diffraction => match slit_structure.diffraction {
+                // let people know which one auto landed on
+                Diffraction::Auto
+                    if spectrum(&slit_structure)
+                        .iter()
+                        .any(|line| is_near_field(&slit_structure, line.wavelength)) =>
+                {
+                    "Auto: Near".to_string()
+                }
+                Diffraction::Auto => "Auto: Far".to_string(),
+                _ => slit_structure.diffraction.to_string(),
+            },
             SlitControl::Input => slit_structure.toggle_input.to_string(),
         };
     }
 }
 
+// matter wave slits are in nanometers, so say so
+fn slit_length(value: Length, slit_structure: &SlitStructure) -> String {
+    match species(&slit_structure.source) {
+        Some(_) => format!("{}nm", value.in_nanometers()),
+        None => value.in_micrometers().to_string(),
+    }
+}
+
 pub fn increment_sep_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &Increment, &SlitControl),
@@ -1,7 +1,13 @@
-use crate::component::{DisplayInfo, Increment, SlitControl, SlitStructure};
+use crate::component::{
+    ControlPage, CurrentPage, DataExport, DetectorControl, DisplayInfo, EmitterControl,
+    ExportButton, Increment, PageButton, PageTitle, ParticleDetector, ParticleEmitter, SlitControl,
+    SlitStructure,
+};
+use crate::launch_seed;
 use bevy::prelude::*;
 
 pub const SLIT_COLOR: Color = Color::rgb(0.43137, 0.27843, 0.17647);
+pub const COVERED_SLIT_COLOR: Color = Color::rgb(0.60980, 0.52353, 0.30392);
 pub const BACKDROUND_COLOR: Color = Color::rgb(0.78824, 0.76863, 0.43137);
 pub const CONTROL_BACKGROUND: Color = Color::rgb(0.27843, 0.18431, 0.12157);
 
@@ -10,6 +16,35 @@ pub const PRESSED_BUTTON: Color = Color::rgb(0.88235, 0.47451, 0.24706);
 pub const BUTTON_TEXT_COLOR: Color = BACKDROUND_COLOR;
 pub const LABEL_TEXT_COLOR: Color = BACKDROUND_COLOR;
 
+// the plate in front of a slit, see SlitStructure::plate
+pub const PLATE_COLOR: Color = Color::rgba(0.55, 0.8, 0.9, 0.7);
+
+// particles the which path detector caught, by slit. cycles for big gratings
+pub const TAG_COLORS: [Color; 4] = [
+    Color::rgb(0.2, 0.8, 1.),
+    Color::rgb(1., 0.35, 0.6),
+    Color::rgb(1., 0.85, 0.2),
+    Color::rgb(0.5, 1., 0.4),
+];
+
+// what the fire one button says
+const FIRE_ONE: &str = "Fire One";
+const SAVE: &str = "Save";
+
+// the controls don't all fit at once, so they're split up into pages
+pub const PAGE_NAMES: [&str; 10] = [
+    "Slits",
+    "One Slit",
+    "Plate",
+    "Polarization",
+    "Source",
+    "Matter",
+    "Screen",
+    "Which Path",
+    "Particles",
+    "Detector",
+];
+
 pub fn get_base() -> NodeBundle {
     NodeBundle {
         style: Style {
@@ -130,8 +165,133 @@ pub fn get_asterisk_txt(txt: &str, clr: Color, asset_server: &Res<AssetServer>)
     })
 }
 
+pub fn get_page_nav() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(120.0), Val::Px(20.)),
+            justify_content: JustifyContent::SpaceBetween,
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect {
+                top: Val::Px(5.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            ..Default::default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    }
+}
+
+pub fn get_page(page: usize) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Auto),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            display: if page == 0 {
+                Display::Flex
+            } else {
+                Display::None
+            },
+            ..default()
+        },
+        background_color: CONTROL_BACKGROUND.into(),
+        ..default()
+    }
+}
+
+// a label with a - value + row under it
+pub fn spawn_stepper(
+    parent: &mut ChildBuilder,
+    label: &str,
+    control: impl Component + Copy,
+    step: f32,
+    value: &str,
+    asset_server: &Res<AssetServer>,
+) {
+    parent.spawn(get_txt(label, LABEL_TEXT_COLOR, asset_server));
+    parent
+        .spawn(get_control_container())
+        .with_children(|parent| {
+            parent
+                .spawn(get_button_bkgnd())
+                .insert(Increment(-step))
+                .insert(control)
+                .with_children(|parent| {
+                    parent.spawn(get_txt("-", BUTTON_TEXT_COLOR, asset_server));
+                });
+            parent.spawn(get_button_bkgnd()).with_children(|parent| {
+                parent
+                    .spawn(get_txt(value, BUTTON_TEXT_COLOR, asset_server))
+                    .insert(control)
+                    .insert(DisplayInfo);
+            });
+            parent
+                .spawn(get_button_bkgnd())
+                .insert(Increment(step))
+                .insert(control)
+                .with_children(|parent| {
+                    parent.spawn(get_txt("+", BUTTON_TEXT_COLOR, asset_server));
+                });
+        });
+}
+
+// a label with one big button under it that flips through the options
+pub fn spawn_toggle(
+    parent: &mut ChildBuilder,
+    label: &str,
+    control: impl Component + Copy,
+    value: &str,
+    asset_server: &Res<AssetServer>,
+) {
+    parent.spawn(get_txt(label, LABEL_TEXT_COLOR, asset_server));
+    parent
+        .spawn(get_control_container())
+        .with_children(|parent| {
+            parent
+                .spawn(get_big_button_bkgnd())
+                .insert(Increment(0.))
+                .insert(control)
+                .with_children(|parent| {
+                    parent
+                        .spawn(get_txt(value, BUTTON_TEXT_COLOR, asset_server))
+                        .insert(DisplayInfo)
+                        .insert(control);
+                });
+        });
+}
+
+pub fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+pub fn running(paused: bool) -> &'static str {
+    if paused {
+        "Paused"
+    } else {
+        "Running"
+    }
+}
+
+// a polarizer's angle in degrees, or Off if there isn't one
+pub fn polarizer_angle(polarizer: Option<f32>) -> String {
+    match polarizer {
+        Some(angle) => angle.to_string(),
+        None => "Off".to_string(),
+    }
+}
+
 pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let defaults = SlitStructure::default();
+    let defaults = SlitStructure {
+        seed: launch_seed(),
+        ..default()
+    };
 
     commands
         .spawn(get_base())
@@ -146,201 +306,390 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 parent
                     .spawn(get_slit_controls_container())
                     .with_children(|parent| {
-                        // SLIT SEPARATOR
-                        parent.spawn(get_txt(
-                            "Slit Separation (micrometers)",
-                            LABEL_TEXT_COLOR,
+                        spawn_toggle(
+                            parent,
+                            "Light / Particles / Wave",
+                            SlitControl::Input,
+                            &defaults.toggle_input.to_string(),
                             &asset_server,
-                        ));
-                        parent
-                            .spawn(get_control_container())
-                            .with_children(|parent| {
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(-1.))
-                                    .insert(SlitControl::Separation)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "-",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
-                                parent.spawn(get_button_bkgnd()).with_children(|parent| {
+                        );
+
+                        // PAGES
+                        parent.spawn(get_page_nav()).with_children(|parent| {
+                            parent
+                                .spawn(get_button_bkgnd())
+                                .insert(PageButton(-1))
+                                .with_children(|parent| {
+                                    parent.spawn(get_txt("<", BUTTON_TEXT_COLOR, &asset_server));
+                                });
+                            parent
+                                .spawn(get_big_button_bkgnd())
+                                .with_children(|parent| {
                                     parent
                                         .spawn(get_txt(
-                                            &defaults.separation.to_string(),
+                                            PAGE_NAMES[0],
                                             BUTTON_TEXT_COLOR,
                                             &asset_server,
                                         ))
-                                        .insert(SlitControl::Separation)
-                                        .insert(DisplayInfo);
+                                        .insert(PageTitle);
                                 });
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(1.))
-                                    .insert(SlitControl::Separation)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "+",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
+                            parent
+                                .spawn(get_button_bkgnd())
+                                .insert(PageButton(1))
+                                .with_children(|parent| {
+                                    parent.spawn(get_txt(">", BUTTON_TEXT_COLOR, &asset_server));
+                                });
+                        });
+
+                        // SLITS
+                        parent
+                            .spawn(get_page(0))
+                            .insert(ControlPage(0))
+                            .with_children(|parent| {
+                                spawn_toggle(
+                                    parent,
+                                    "Aperture",
+                                    SlitControl::Aperture,
+                                    &defaults.aperture.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Slit Separation (micrometers)",
+                                    SlitControl::Separation,
+                                    1.,
+                                    &defaults.separation.in_micrometers().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Number of Slits",
+                                    SlitControl::SlitCount,
+                                    1.,
+                                    &defaults.slit_count.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Combine Open Slits",
+                                    SlitControl::Coherence,
+                                    &defaults.coherence.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Slit Width (micrometers)",
+                                    SlitControl::Width,
+                                    1.,
+                                    &defaults.slit_width.in_micrometers().to_string(),
+                                    &asset_server,
+                                );
                             });
-                        // SLIT WIDTH
-                        parent.spawn(get_txt(
-                            "Slit Width (micrometers)",
-                            LABEL_TEXT_COLOR,
-                            &asset_server,
-                        ));
+
+                        // ONE SLIT
                         parent
-                            .spawn(get_control_container())
+                            .spawn(get_page(1))
+                            .insert(ControlPage(1))
                             .with_children(|parent| {
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(-1.))
-                                    .insert(SlitControl::Width)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "-",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
-                                parent.spawn(get_button_bkgnd()).with_children(|parent| {
-                                    parent
-                                        .spawn(get_txt(
-                                            &defaults.slit_width.to_string(),
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ))
-                                        .insert(SlitControl::Width)
-                                        .insert(DisplayInfo);
-                                });
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(1.))
-                                    .insert(SlitControl::Width)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "+",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
+                                spawn_stepper(
+                                    parent,
+                                    "Slit",
+                                    SlitControl::SelectedSlit,
+                                    1.,
+                                    &(defaults.selected_slit + 1).to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Its Width (times slit width)",
+                                    SlitControl::SelectedWidth,
+                                    0.2,
+                                    &defaults.width_scales[0].to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Its Transmission",
+                                    SlitControl::SelectedTransmission,
+                                    0.1,
+                                    &defaults.transmissions[0].to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Its Polarizer (degrees)",
+                                    SlitControl::SelectedPolarizer,
+                                    &polarizer_angle(defaults.polarizers[0]),
+                                    &asset_server,
+                                );
                             });
-                        // WAVELENGTH
-                        parent.spawn(get_txt("Wavelength* (nm)", LABEL_TEXT_COLOR, &asset_server));
+
+                        // PLATE
                         parent
-                            .spawn(get_control_container())
+                            .spawn(get_page(2))
+                            .insert(ControlPage(2))
                             .with_children(|parent| {
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(-10.))
-                                    .insert(SlitControl::Wavelength)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "-",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
-                                parent.spawn(get_button_bkgnd()).with_children(|parent| {
-                                    parent
-                                        .spawn(get_txt(
-                                            &defaults.slit_width.to_string(),
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ))
-                                        .insert(SlitControl::Wavelength)
-                                        .insert(DisplayInfo);
-                                });
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(10.))
-                                    .insert(SlitControl::Wavelength)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "+",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
+                                spawn_toggle(
+                                    parent,
+                                    "Plate",
+                                    SlitControl::Plate,
+                                    &defaults.plate.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "In Front of Slit",
+                                    SlitControl::PlateSlit,
+                                    1.,
+                                    &(defaults.plate_slit + 1).to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Phase Shift (degrees)",
+                                    SlitControl::PlatePhase,
+                                    30.,
+                                    &defaults.plate_phase.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Glass Thickness (micrometers)",
+                                    SlitControl::PlateThickness,
+                                    0.1,
+                                    &defaults.plate_thickness.in_micrometers().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Refractive Index",
+                                    SlitControl::PlateIndex,
+                                    0.05,
+                                    &defaults.plate_index.to_string(),
+                                    &asset_server,
+                                );
                             });
 
-                        // DISTANCE TO SCREEN
-                        parent.spawn(get_txt(
-                            "Distance to Screen (m)",
-                            LABEL_TEXT_COLOR,
-                            &asset_server,
-                        ));
+                        // POLARIZATION
                         parent
-                            .spawn(get_control_container())
+                            .spawn(get_page(3))
+                            .insert(ControlPage(3))
                             .with_children(|parent| {
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(-10.))
-                                    .insert(SlitControl::ScreenDistance)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "-",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
-                                parent.spawn(get_button_bkgnd()).with_children(|parent| {
-                                    parent
-                                        .spawn(get_txt(
-                                            &defaults.slit_width.to_string(),
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ))
-                                        .insert(SlitControl::ScreenDistance)
-                                        .insert(DisplayInfo);
-                                });
-                                parent
-                                    .spawn(get_button_bkgnd())
-                                    .insert(Increment(10.))
-                                    .insert(SlitControl::ScreenDistance)
-                                    .with_children(|parent| {
-                                        parent.spawn(get_txt(
-                                            "+",
-                                            BUTTON_TEXT_COLOR,
-                                            &asset_server,
-                                        ));
-                                    });
+                                spawn_toggle(
+                                    parent,
+                                    "Source Polarization",
+                                    SlitControl::Polarization,
+                                    &defaults.polarization.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Analyzer (degrees)",
+                                    SlitControl::Analyzer,
+                                    &polarizer_angle(defaults.analyzer),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Quantum Eraser Slits",
+                                    SlitControl::Eraser,
+                                    on_off(defaults.is_eraser()),
+                                    &asset_server,
+                                );
                             });
 
-                        // Light vs Particles
-                        parent.spawn(get_txt(
-                            "Light vs Particles",
-                            LABEL_TEXT_COLOR,
-                            &asset_server,
-                        ));
+                        // SOURCE
+                        parent
+                            .spawn(get_page(4))
+                            .insert(ControlPage(4))
+                            .with_children(|parent| {
+                                spawn_toggle(
+                                    parent,
+                                    "Light Source",
+                                    SlitControl::Source,
+                                    &defaults.source.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Laser Wavelength* (nm)",
+                                    SlitControl::Wavelength,
+                                    10.,
+                                    &defaults.wavelength.in_nanometers().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Source Width (micrometers)",
+                                    SlitControl::SourceWidth,
+                                    100.,
+                                    &defaults.source_width.in_micrometers().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Source to Slits (cm)",
+                                    SlitControl::SourceDistance,
+                                    5.,
+                                    &defaults.source_distance.in_centimeters().to_string(),
+                                    &asset_server,
+                                );
+                            });
+
+                        // MATTER
+                        parent
+                            .spawn(get_page(5))
+                            .insert(ControlPage(5))
+                            .with_children(|parent| {
+                                spawn_stepper(
+                                    parent,
+                                    "Electron Voltage (kV)",
+                                    SlitControl::Voltage,
+                                    5.,
+                                    &defaults.voltage.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Atom Speed (m/s)",
+                                    SlitControl::Velocity,
+                                    100.,
+                                    &defaults.velocity.to_string(),
+                                    &asset_server,
+                                );
+                            });
+
+                        // SCREEN
+                        parent
+                            .spawn(get_page(6))
+                            .insert(ControlPage(6))
+                            .with_children(|parent| {
+                                spawn_stepper(
+                                    parent,
+                                    "Distance to Screen (m)",
+                                    SlitControl::ScreenDistance,
+                                    10.,
+                                    &defaults.screen_distance.meters().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Diffraction",
+                                    SlitControl::Diffraction,
+                                    &defaults.diffraction.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Particle Seed",
+                                    SlitControl::Seed,
+                                    1.,
+                                    &defaults.seed.to_string(),
+                                    &asset_server,
+                                );
+                            });
+
+                        // WHICH PATH
+                        parent
+                            .spawn(get_page(7))
+                            .insert(ControlPage(7))
+                            .with_children(|parent| {
+                                spawn_stepper(
+                                    parent,
+                                    "Detector at Slits",
+                                    SlitControl::WhichPath,
+                                    0.1,
+                                    &defaults.which_path.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Color Caught Particles",
+                                    SlitControl::TagSlits,
+                                    on_off(defaults.tag_slits),
+                                    &asset_server,
+                                );
+                            });
+
+                        // PARTICLES
+                        let emitter = ParticleEmitter::default();
+                        parent
+                            .spawn(get_page(8))
+                            .insert(ControlPage(8))
+                            .with_children(|parent| {
+                                spawn_stepper(
+                                    parent,
+                                    "Particles per Second",
+                                    EmitterControl::Rate,
+                                    1.,
+                                    &emitter.per_second().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_stepper(
+                                    parent,
+                                    "Particles Kept",
+                                    EmitterControl::Kept,
+                                    1.,
+                                    &emitter.limit().to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Screen Shows",
+                                    EmitterControl::View,
+                                    &emitter.view.to_string(),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "Emitter",
+                                    EmitterControl::Pause,
+                                    running(emitter.paused),
+                                    &asset_server,
+                                );
+                                spawn_toggle(
+                                    parent,
+                                    "One at a Time",
+                                    EmitterControl::FireOne,
+                                    FIRE_ONE,
+                                    &asset_server,
+                                );
+                            });
+
+                        // DETECTOR
+                        let detector = ParticleDetector::default();
                         parent
-                            .spawn(get_control_container())
+                            .spawn(get_page(9))
+                            .insert(ControlPage(9))
                             .with_children(|parent| {
-                                parent
-                                    .spawn(get_big_button_bkgnd())
-                                    .insert(Increment(0.))
-                                    .insert(SlitControl::Input)
-                                    .with_children(|parent| {
-                                        parent
-                                            .spawn(get_txt(
-                                                &defaults.toggle_input.to_string(),
-                                                BUTTON_TEXT_COLOR,
-                                                &asset_server,
-                                            ))
-                                            .insert(DisplayInfo)
-                                            .insert(SlitControl::Input);
-                                    });
+                                for (label, control) in [
+                                    ("Quantum Efficiency (%)", DetectorControl::Efficiency),
+                                    ("Dark Counts per Second", DetectorControl::DarkRate),
+                                    ("Blur (% of screen width)", DetectorControl::Blur),
+                                    ("Pixels Across", DetectorControl::Pixels),
+                                ] {
+                                    spawn_stepper(
+                                        parent,
+                                        label,
+                                        control,
+                                        1.,
+                                        &detector_setting(&detector, &control),
+                                        &asset_server,
+                                    );
+                                }
+                                spawn_toggle(
+                                    parent,
+                                    "Hits and Profile to CSV",
+                                    ExportButton,
+                                    SAVE,
+                                    &asset_server,
+                                );
                             });
                     });
 
                 // asterisk
                 parent.spawn(get_asterisk_txt(
-                    "*note - colors not exactly to scale",
+                    "*note - colors not exactly to scale. click a slit to cover it",
                     LABEL_TEXT_COLOR,
                     &asset_server,
                 ));
@@ -349,3 +698,167 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands.insert_resource(defaults);
 }
+
+pub fn change_page_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &PageButton),
+        Changed<Interaction>,
+    >,
+    mut current_page: ResMut<CurrentPage>,
+) {
+    for (interaction, mut color, page_button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                let pages = PAGE_NAMES.len() as i32;
+                current_page.0 = (current_page.0 as i32 + page_button.0).rem_euclid(pages) as usize;
+            }
+            _ => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// same as increment_sep_system, for the emitter's buttons
+pub fn emitter_button_system(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &Increment,
+            &EmitterControl,
+        ),
+        Changed<Interaction>,
+    >,
+    mut emitter: ResMut<ParticleEmitter>,
+) {
+    for (interaction, mut color, incr, control) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                emitter.add_val(control, incr.0);
+            }
+            _ => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+pub fn update_emitter_display(
+    mut display_query: Query<(&mut Text, &EmitterControl), With<DisplayInfo>>,
+    emitter: Res<ParticleEmitter>,
+) {
+    for (mut text, control) in display_query.iter_mut() {
+        text.sections[0].value = match control {
+            EmitterControl::Rate => emitter.per_second().to_string(),
+            EmitterControl::Kept => emitter.limit().to_string(),
+            EmitterControl::View => emitter.view.to_string(),
+            EmitterControl::Pause => running(emitter.paused).to_string(),
+            EmitterControl::FireOne => FIRE_ONE.to_string(),
+        };
+    }
+}
+
+// same again, for the detector's
+pub fn detector_button_system(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &Increment,
+            &DetectorControl,
+        ),
+        Changed<Interaction>,
+    >,
+    mut detector: ResMut<ParticleDetector>,
+) {
+    for (interaction, mut color, incr, control) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                detector.add_val(control, incr.0);
+            }
+            _ => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+pub fn update_detector_display(
+    mut display_query: Query<(&mut Text, &DetectorControl), With<DisplayInfo>>,
+    detector: Res<ParticleDetector>,
+) {
+    for (mut text, control) in display_query.iter_mut() {
+        text.sections[0].value = detector_setting(&detector, control);
+    }
+}
+
+// what the detector's display shows for `control`
+fn detector_setting(detector: &ParticleDetector, control: &DetectorControl) -> String {
+    let model = detector.model();
+    match control {
+        DetectorControl::Efficiency => (model.efficiency * 100.).round().to_string(),
+        DetectorControl::DarkRate => model.dark_rate.to_string(),
+        DetectorControl::Blur => format!("{:.1}", model.blur * 100.),
+        DetectorControl::Pixels => match model.pixels {
+            Some(pixels) => pixels.to_string(),
+            None => "Off".to_string(),
+        },
+    }
+}
+
+pub fn export_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ExportButton),
+        Changed<Interaction>,
+    >,
+    mut export: ResMut<DataExport>,
+) {
+    for (interaction, mut color, _) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                export.requested = true;
+            }
+            _ => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// says how the last export went, the files' names are in the working directory
+pub fn update_export_display(
+    mut display_query: Query<&mut Text, (With<ExportButton>, With<DisplayInfo>)>,
+    export: Res<DataExport>,
+) {
+    for mut text in display_query.iter_mut() {
+        text.sections[0].value = match export.last {
+            None => SAVE,
+            Some(Ok(_)) => "Saved",
+            Some(Err(_)) => "Failed",
+        }
+        .to_string();
+    }
+}
+
+pub fn update_pages(
+    current_page: Res<CurrentPage>,
+    mut page_query: Query<(&mut Style, &ControlPage)>,
+    mut title_query: Query<&mut Text, With<PageTitle>>,
+) {
+    for (mut style, page) in page_query.iter_mut() {
+        style.display = if page.0 == current_page.0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for mut text in title_query.iter_mut() {
+        text.sections[0].value = PAGE_NAMES[current_page.0].to_string();
+    }
+}
@@ -0,0 +1,123 @@
+// lengths that know what they're measured in. each one keeps the unit it's usually
+// set in, so the numbers on the buttons stay round, and only turns into meters when
+// the physics asks for it. mixing up a wavelength and a slit width won't compile
+
+use std::ops::{Add, Div, Mul, Sub};
+
+// the only place these conversions get written down. all exact in floats,
+// and dividing by them rounds properly where multiplying by 1e-9 wouldn't
+const NANOMETERS_PER_METER: f32 = 1e9;
+const NANOMETERS_PER_MICROMETER: f32 = 1000.;
+const CENTIMETERS_PER_METER: f32 = 100.;
+
+/// sizes at the slits, like their width and how far apart they are
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Length(f32); // nanometers, so whole micrometers stay whole numbers too
+
+impl Length {
+    pub const fn nanometers(value: f32) -> Self {
+        Length(value)
+    }
+
+    pub fn micrometers(value: f32) -> Self {
+        Length(value * NANOMETERS_PER_MICROMETER)
+    }
+
+    pub fn meters(self) -> f32 {
+        self.0 / NANOMETERS_PER_METER
+    }
+
+    pub fn in_nanometers(self) -> f32 {
+        self.0
+    }
+
+    pub fn in_micrometers(self) -> f32 {
+        self.0 / NANOMETERS_PER_MICROMETER
+    }
+}
+
+/// distances across the bench, like from the slits to the screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Distance(f32); // centimeters
+
+impl Distance {
+    pub const fn centimeters(value: f32) -> Self {
+        Distance(value)
+    }
+
+    pub fn meters(self) -> f32 {
+        self.0 / CENTIMETERS_PER_METER
+    }
+
+    pub fn in_centimeters(self) -> f32 {
+        self.0
+    }
+}
+
+/// wavelengths of light, or of matter waves
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Wavelength(f32); // nanometers
+
+impl Wavelength {
+    pub const fn nanometers(value: f32) -> Self {
+        Wavelength(value)
+    }
+
+    pub fn from_meters(value: f32) -> Self {
+        Wavelength(value * NANOMETERS_PER_METER)
+    }
+
+    pub fn meters(self) -> f32 {
+        self.0 / NANOMETERS_PER_METER
+    }
+
+    pub fn in_nanometers(self) -> f32 {
+        self.0
+    }
+
+    pub fn in_micrometers(self) -> f32 {
+        self.0 / NANOMETERS_PER_MICROMETER
+    }
+}
+
+// the same few operators for each, none of them mix types
+macro_rules! unit_ops {
+    ($unit:ident) => {
+        impl Add for $unit {
+            type Output = $unit;
+
+            fn add(self, rhs: $unit) -> $unit {
+                $unit(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $unit {
+            type Output = $unit;
+
+            fn sub(self, rhs: $unit) -> $unit {
+                $unit(self.0 - rhs.0)
+            }
+        }
+
+        impl Mul<f32> for $unit {
+            type Output = $unit;
+
+            fn mul(self, rhs: f32) -> $unit {
+                $unit(self.0 * rhs)
+            }
+        }
+
+        /// how many of `rhs` fit in it
+        impl Div for $unit {
+            type Output = f32;
+
+            fn div(self, rhs: $unit) -> f32 {
+                self.0 / rhs.0
+            }
+        }
+    };
+}
+
+unit_ops!(Length);
+unit_ops!(Distance);
+unit_ops!(Wavelength);